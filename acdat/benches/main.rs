@@ -1,14 +1,13 @@
 #![feature(test)]
+extern crate linter;
 extern crate rand;
 extern crate test;
+use linter::{hyphenate, Hyphenator, Options};
 use rand::{Rng, XorShiftRng};
 use std::fs::File;
 use std::io::Read;
 use test::Bencher;
 
-mod linter;
-use linter::hyphen;
-
 extern crate hyphenation;
 use hyphenation::Hyphenation;
 use hyphenation::Language::English_US;
@@ -25,13 +24,40 @@ fn prepare(length: usize) -> Vec<String> {
 }
 
 macro_rules! bench {
-    ($a:ident, $b:ident, $length:expr) => {
+    ($a:ident, $h:ident, $p:ident, $b:ident, $length:expr) => {
         #[bench]
         fn $a(b: &mut Bencher) {
             let data = prepare($length);
+            let options = Options::default();
             b.iter(|| {
                 for word in data.clone() {
-                    hyphen(&word);
+                    hyphenate(&word, &options);
+                }
+            });
+        }
+
+        #[bench]
+        fn $h(b: &mut Bencher) {
+            let data = prepare($length);
+            let mut hyphenator = Hyphenator::new();
+            let mut result = String::with_capacity(32);
+            b.iter(|| {
+                for word in data.iter() {
+                    result.clear();
+                    hyphenator
+                        .hyphenate_to(&mut result, word, "\u{00AD}")
+                        .unwrap();
+                }
+            });
+        }
+
+        #[bench]
+        fn $p(b: &mut Bencher) {
+            let data = prepare($length);
+            let mut hyphenator = Hyphenator::new();
+            b.iter(|| {
+                for word in data.iter() {
+                    test::black_box(hyphenator.breaks(word).count());
                 }
             });
         }
@@ -50,17 +76,17 @@ macro_rules! bench {
     };
 }
 
-bench!(bench05_acdat, bench05_crate, 5);
-bench!(bench06_acdat, bench06_crate, 6);
-bench!(bench07_acdat, bench07_crate, 7);
-bench!(bench08_acdat, bench08_crate, 8);
-bench!(bench09_acdat, bench09_crate, 9);
-bench!(bench10_acdat, bench10_crate, 10);
-bench!(bench11_acdat, bench11_crate, 11);
-bench!(bench12_acdat, bench12_crate, 12);
-bench!(bench13_acdat, bench13_crate, 13);
-bench!(bench14_acdat, bench14_crate, 14);
-bench!(bench15_acdat, bench15_crate, 15);
-bench!(bench16_acdat, bench16_crate, 16);
-bench!(bench17_acdat, bench17_crate, 17);
-bench!(bench18_acdat, bench18_crate, 18);
+bench!(bench05_acdat, bench05_handle, bench05_breaks, bench05_crate, 5);
+bench!(bench06_acdat, bench06_handle, bench06_breaks, bench06_crate, 6);
+bench!(bench07_acdat, bench07_handle, bench07_breaks, bench07_crate, 7);
+bench!(bench08_acdat, bench08_handle, bench08_breaks, bench08_crate, 8);
+bench!(bench09_acdat, bench09_handle, bench09_breaks, bench09_crate, 9);
+bench!(bench10_acdat, bench10_handle, bench10_breaks, bench10_crate, 10);
+bench!(bench11_acdat, bench11_handle, bench11_breaks, bench11_crate, 11);
+bench!(bench12_acdat, bench12_handle, bench12_breaks, bench12_crate, 12);
+bench!(bench13_acdat, bench13_handle, bench13_breaks, bench13_crate, 13);
+bench!(bench14_acdat, bench14_handle, bench14_breaks, bench14_crate, 14);
+bench!(bench15_acdat, bench15_handle, bench15_breaks, bench15_crate, 15);
+bench!(bench16_acdat, bench16_handle, bench16_breaks, bench16_crate, 16);
+bench!(bench17_acdat, bench17_handle, bench17_breaks, bench17_crate, 17);
+bench!(bench18_acdat, bench18_handle, bench18_breaks, bench18_crate, 18);
@@ -1,10 +1,9 @@
 extern crate fnv;
+extern crate linter;
+use linter::{hyphenate, Options};
 use std::fs::File;
 use std::io::Read;
 
-mod linter;
-use linter::hyphen;
-
 extern crate hyphenation;
 use hyphenation::Hyphenation;
 use hyphenation::Language::English_US;
@@ -20,10 +19,11 @@ fn prepare() -> String {
 fn test_equal() {
     let data = prepare();
     let english_us = hyphenation::load(English_US).unwrap();
+    let options = Options::default();
 
     for word in data.split("\n") {
         let g = word.hyphenate(&english_us);
         let s: String = g.punctuate().collect();
-        assert_eq!(hyphen(word), s);
+        assert_eq!(hyphenate(word, &options), s);
     }
 }
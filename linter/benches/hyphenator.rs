@@ -0,0 +1,81 @@
+//! Throughput of points into a buffer allocated per word, as `detect` did before, against points
+//! into a reused buffer and the breaks of the Hyphenator handle, on the words of
+//! `acdat/benches/bench.dict` by length.
+//!
+//! ```plain
+//! $ cargo bench -p linter
+//! ```
+extern crate linter;
+use linter::{points, Hyphenator};
+use std::fs;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Words per length, picked at random.
+const WORDS: usize = 10000;
+/// Rounds each measurement is the best of.
+const ROUNDS: usize = 20;
+
+/// Returns WORDS words of length picked by xorshift, stable across runs.
+fn prepare(dict: &str, length: usize) -> Vec<&str> {
+    let words: Vec<&str> = dict.lines().filter(|x| x.len() == length).collect();
+    let mut state: u32 = 2_463_534_242;
+    (0..WORDS)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            words[state as usize % words.len()]
+        })
+        .collect()
+}
+
+/// Returns the fastest of ROUNDS runs of f.
+fn measure<F: FnMut()>(mut f: F) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../acdat/benches/bench.dict");
+    let dict = fs::read_to_string(path).unwrap();
+    println!("length  allocating  reused      handle");
+    for length in 5..19 {
+        let data = prepare(&dict, length);
+        let allocating = measure(|| {
+            for word in data.iter() {
+                let mut result = vec![0; word.len() + 1];
+                points(word, &mut result);
+                black_box(&result);
+            }
+        });
+        let mut result = Vec::new();
+        let reused = measure(|| {
+            for word in data.iter() {
+                result.clear();
+                result.resize(word.len() + 1, 0);
+                points(word, &mut result);
+                black_box(&result);
+            }
+        });
+        let mut hyphenator = Hyphenator::new();
+        let handle = measure(|| {
+            for word in data.iter() {
+                black_box(hyphenator.breaks(word).count());
+            }
+        });
+        println!(
+            "{:>6}  {:>7.1} ns  {:>7.1} ns  {:>7.1} ns",
+            length,
+            allocating.as_nanos() as f64 / WORDS as f64,
+            reused.as_nanos() as f64 / WORDS as f64,
+            handle.as_nanos() as f64 / WORDS as f64
+        );
+    }
+}
@@ -0,0 +1,404 @@
+extern crate linter;
+use linter::{
+    classify, hyphenate, lint, lint_with, mismatches, points, Context, Hyphenator, Hyphens, Io,
+    Linter, Mismatch, Options, Punctuation, Scripts, Spacing, Stream, Typography,
+};
+
+const NONE: Options = Options {
+    hyphens: Hyphens::Soft,
+    spacing: Spacing::Thin,
+    acronym: false,
+    camel_case: false,
+    identifier: false,
+    alphanumeric: false,
+    chinese: true,
+    kana: true,
+    hangul: true,
+    typography: Typography::None,
+    punctuation: Punctuation::Keep,
+};
+
+#[test]
+fn test_hyphen() {
+    let options = Options::default();
+    assert_eq!(
+        "&gt;这是\u{2009}Hy\u{00AD}phen\u{00AD}ation\u{2009}的文字",
+        lint(">这是Hyphenation的文字", &options)
+    );
+    assert_eq!(
+        "Hy\u{00AD}phen\u{00AD}ation.",
+        lint("Hyphenation.", &options)
+    );
+}
+
+#[test]
+fn test_points() {
+    let mut result = [1; 6];
+    points("table", &mut result);
+    assert_eq!([2, 0, 4, 4, 2, 0], result);
+    points("a_b_c", &mut result);
+    assert_eq!([0; 6], result);
+
+    let mut hyphenator = Hyphenator::new();
+    let breaks = |hyphenator: &mut Hyphenator, word| hyphenator.breaks(word).collect::<Vec<_>>();
+    assert_eq!(vec![2, 6], breaks(&mut hyphenator, "Hyphenation"));
+    assert_eq!(vec![2], breaks(&mut hyphenator, "table"));
+    assert_eq!(vec![4, 6], breaks(&mut hyphenator, "philanthropic"));
+    assert_eq!(Vec::<usize>::new(), breaks(&mut hyphenator, "project"));
+    assert_eq!(Vec::<usize>::new(), breaks(&mut hyphenator, "word"));
+    assert_eq!(
+        Vec::<usize>::new(),
+        breaks(&mut hyphenator, "中文Hyphenation")
+    );
+
+    let mut result = String::new();
+    for word in &["Hyphenation", "table", "utf-8", "associate"] {
+        hyphenator.hyphenate_to(&mut result, word, "-").unwrap();
+        result.push(' ');
+    }
+    assert_eq!("Hy-phen-ation ta-ble utf-8 as-so-ciate ", result);
+}
+
+#[test]
+fn test_hyphens() {
+    let lints = |hyphens| {
+        let options = Options { hyphens, ..NONE };
+        [lint("Hyphenation", &options), lint("table", &options)]
+    };
+    assert_eq!(
+        ["Hy\u{00AD}phen\u{00AD}ation", "ta\u{00AD}ble"],
+        lints(Hyphens::Soft)
+    );
+    assert_eq!(
+        ["Hy&shy;phen&shy;ation", "ta&shy;ble"],
+        lints(Hyphens::Entity)
+    );
+    assert_eq!(["Hy<wbr>phen<wbr>ation", "ta<wbr>ble"], lints(Hyphens::Wbr));
+    assert_eq!(["Hyphenation", "table"], lints(Hyphens::None));
+    assert_eq!(["Hy-phen-ation", "ta-ble"], lints(Hyphens::Visible));
+    for &hyphens in &[
+        Hyphens::Entity,
+        Hyphens::Wbr,
+        Hyphens::None,
+        Hyphens::Visible,
+    ] {
+        let soft = lints(Hyphens::Soft);
+        assert_eq!(lints(hyphens)[0], hyphens.apply(&soft[0]));
+    }
+    let html = "<a title=\"ta\u{00AD}ble>\">ta\u{00AD}ble</a>";
+    assert_eq!(
+        "<a title=\"ta\u{00AD}ble>\">ta<wbr>ble</a>",
+        Hyphens::Wbr.apply(html)
+    );
+    let html = "<!--post:ta\u{00AD}ble-->ta\u{00AD}ble<script>\"ta\u{00AD}ble\"</script>";
+    assert_eq!(
+        "<!--post:ta\u{00AD}ble-->ta<wbr>ble<script>\"ta\u{00AD}ble\"</script>",
+        Hyphens::Wbr.apply(html)
+    );
+    assert!(Hyphens::Wbr.plain() == Hyphens::Soft);
+    assert!(Options::default().attribute().hyphens == Hyphens::None);
+}
+
+#[test]
+fn test_scripts() {
+    let options = Options::default();
+    assert_eq!(
+        "ひらがな\u{2009}Hy\u{00AD}phen\u{00AD}ation\u{2009}カタカナ",
+        lint("ひらがなHyphenationカタカナ", &options)
+    );
+    assert_eq!(
+        "한국어\u{2009}2018\u{2009}년",
+        lint("한국어2018년", &options)
+    );
+    assert_eq!(
+        "日本語のtext",
+        lint(
+            "日本語のtext",
+            &Options {
+                kana: false,
+                ..options
+            }
+        )
+    );
+    assert_eq!("漢字かな한글", lint("漢字かな한글", &options));
+    assert_eq!("中文 text", lint("中文 text", &options));
+}
+
+#[test]
+fn test_spacing() {
+    let lints = |spacing| {
+        let options = Options { spacing, ..NONE };
+        lint("中文Rust中文", &options)
+    };
+    assert_eq!("中文\u{2009}Rust\u{2009}中文", lints(Spacing::Thin));
+    assert_eq!("中文 Rust 中文", lints(Spacing::Space));
+    assert_eq!("中文\u{200A}Rust\u{200A}中文", lints(Spacing::Hair));
+    assert_eq!(
+        "中文<span class=\"cjk-gap\"></span>Rust<span class=\"cjk-gap\"></span>中文",
+        lints(Spacing::Span)
+    );
+    assert_eq!("中文Rust中文", lints(Spacing::None));
+    let options = Options {
+        spacing: Spacing::Span,
+        ..NONE
+    }
+    .plain();
+    assert_eq!(
+        "中文\u{2009}Rust\u{2009}中文",
+        lint("中文Rust中文", &options)
+    );
+}
+
+#[test]
+fn test_punctuation() {
+    let options = Options::default();
+    let cases = [
+        ("中文，Rust。", "中文，Rust。"),
+        ("Rust，中文。", "Rust，中文。"),
+        ("中文（Rust）中文", "中文（Rust）中文"),
+        ("中文(Rust)中文", "中文\u{2009}(Rust)\u{2009}中文"),
+        ("中文[1]中文", "中文\u{2009}[1]\u{2009}中文"),
+        ("占50%的", "占\u{2009}50%\u{2009}的"),
+        ("气温30°C以上", "气温\u{2009}30°C\u{2009}以上"),
+        ("气温30℃以上", "气温\u{2009}30℃\u{2009}以上"),
+        ("花了$100元", "花了\u{2009}$100\u{2009}元"),
+        ("花了€5", "花了\u{2009}€5"),
+        ("重5kg的", "重\u{2009}5kg\u{2009}的"),
+        ("f(x)", "f(x)"),
+    ];
+    for &(text, result) in cases.iter() {
+        assert_eq!(result, lint(text, &options));
+    }
+}
+
+#[test]
+fn test_acronym() {
+    let options = Options {
+        acronym: true,
+        ..NONE
+    };
+    assert_eq!("HTTPS", lint("HTTPS", &options));
+    assert_eq!("UNICODE", lint("UNICODE", &options));
+    assert_ne!("UNICODE", lint("UNICODE", &NONE));
+}
+
+#[test]
+fn test_camel_case() {
+    let options = Options {
+        camel_case: true,
+        ..NONE
+    };
+    assert_eq!("JavaScript", lint("JavaScript", &options));
+    assert_eq!("RefCell", lint("RefCell", &options));
+    assert_eq!("WebAssembly", lint("WebAssembly", &options));
+    assert_ne!("WebAssembly", lint("WebAssembly", &NONE));
+    assert_ne!("Hyphenation", lint("Hyphenation", &options));
+}
+
+#[test]
+fn test_identifier() {
+    let options = Options {
+        identifier: true,
+        ..NONE
+    };
+    assert_eq!("www.example.com", lint("www.example.com", &options));
+    assert_eq!("https://example.com", lint("https://example.com", &options));
+    assert_eq!("hyphenation_table", lint("hyphenation_table", &options));
+    assert_eq!("src/hyphenation", lint("src/hyphenation", &options));
+    assert_ne!("hyphenation.", lint("hyphenation.", &options));
+    assert_ne!("example.com", lint("example.com", &NONE));
+}
+
+#[test]
+fn test_alphanumeric() {
+    let options = Options {
+        alphanumeric: true,
+        ..NONE
+    };
+    assert_eq!("hyphenation2", lint("hyphenation2", &options));
+    assert_eq!("2hyphenation", lint("2hyphenation", &options));
+    assert_ne!("hyphenation2", lint("hyphenation2", &NONE));
+}
+
+#[test]
+fn test_classify() {
+    let runs: Vec<_> = classify("中文Rust 2018かな").collect();
+    assert_eq!(
+        vec![
+            (Scripts::Chinese, "中文"),
+            (Scripts::English, "Rust"),
+            (Scripts::Unknown, " "),
+            (Scripts::Numbers, "2018"),
+            (Scripts::Kana, "かな"),
+        ],
+        runs
+    );
+    assert_eq!(None, classify("").next());
+}
+
+#[test]
+fn test_hyphenate() {
+    let options = Options::default();
+    assert_eq!(
+        "<这是Hy\u{00AD}phen\u{00AD}ation的文字>",
+        hyphenate("<这是Hyphenation的文字>", &options)
+    );
+    assert_eq!("HTTPS", hyphenate("HTTPS", &options));
+}
+
+#[test]
+fn test_typography() {
+    let curly = Options {
+        typography: Typography::Curly,
+        ..NONE
+    };
+    let corner = Options {
+        typography: Typography::Corner,
+        ..NONE
+    };
+    assert_eq!("“Rust” ‘Rust’", lint("\"Rust\" 'Rust'", &curly));
+    assert_eq!("他说“中文”。", lint("他说\"中文\"。", &curly));
+    assert_eq!("他说「中文」。", lint("他说\"中文\"。", &corner));
+    assert_eq!("「中文『引用』」", lint("\"中文'引用'\"", &corner));
+    assert_eq!("“Rust” and “中文”", lint("\"Rust\" and \"中文\"", &curly));
+    assert_eq!(
+        "“Rust” and 「中文」",
+        lint("\"Rust\" and \"中文\"", &corner)
+    );
+    assert_eq!("don’t ’90s Rust’s", lint("don't '90s Rust's", &curly));
+    assert_eq!("‘don’t’", lint("'don't'", &curly));
+    assert_eq!("1–2 Rust—中文", lint("1--2 Rust---中文", &curly));
+    assert_eq!("well-known ----", lint("well-known ----", &curly));
+    assert_eq!(
+        "Wait… Wait.. Rust.rs",
+        lint("Wait... Wait.. Rust.rs", &curly)
+    );
+    assert_eq!(
+        "5\u{00A0}kg 5\u{00A0}GHz 5 cats",
+        lint("5 kg 5 GHz 5 cats", &curly)
+    );
+    assert_eq!("&#34;Rust&#34; 5 kg...", lint("\"Rust\" 5 kg...", &NONE));
+    assert_eq!("\"Rust\"", hyphenate("\"Rust\"", &curly));
+
+    let mut stream = Stream::new(String::new());
+    stream.push_txt("\"Wait..", &curly);
+    stream.push_raw("<em>");
+    stream.push_txt("Rust\"", &curly);
+    assert_eq!("“Wait..<em>Rust”", stream.into_inner().unwrap());
+
+    let mut stream = Stream::new(String::new());
+    stream.push_txt("Wait..", &curly);
+    stream.push_txt(".", &curly);
+    assert_eq!("Wait…", stream.into_inner().unwrap());
+
+    let mut result = String::new();
+    let mut context = Context::default();
+    lint_with(&mut result, "'", &curly, &mut context).unwrap();
+    lint_with(&mut result, "Rust", &curly, &mut context).unwrap();
+    lint_with(&mut result, "'", &curly, &mut context).unwrap();
+    context.push(' ');
+    lint_with(&mut result, "'", &curly, &mut context).unwrap();
+    assert_eq!("‘Rust’‘", result);
+}
+
+#[test]
+fn test_width() {
+    let fix = Options {
+        punctuation: Punctuation::Fix,
+        ..NONE
+    };
+    assert_eq!("中文，中文。", lint("中文, 中文.", &fix));
+    assert_eq!("Rust：中文；中文！", lint("Rust:中文;中文!", &fix));
+    assert_eq!("中文 （中文）中文", lint("中文 (中文) 中文", &fix));
+    assert_eq!(
+        "中文\u{2009}(Rust)\u{2009}中文",
+        lint("中文(Rust)中文", &fix)
+    );
+    assert_eq!("Rust,Rust.Rust", lint("Rust，Rust。Rust", &fix));
+    assert_eq!("Rust (Rust) Rust", lint("Rust （Rust） Rust", &fix));
+    assert_eq!("中文，Rust。", lint("中文，Rust。", &fix));
+    assert_eq!("中文...", lint("中文...", &fix));
+    assert_eq!("配置文件.toml", lint("配置文件.toml", &fix));
+    assert_eq!("中文。Rust", lint("中文. Rust", &fix));
+    assert_eq!(
+        "中文…",
+        lint(
+            "中文...",
+            &Options {
+                typography: Typography::Curly,
+                ..fix
+            }
+        )
+    );
+    assert_eq!("3.14, 12:30", lint("3.14, 12:30", &fix));
+    assert_eq!("中文, 中文.", lint("中文, 中文.", &NONE));
+
+    assert_eq!(0, mismatches("配置文件.toml").count());
+    let result: Vec<Mismatch> = mismatches("中文, Rust，Rust (中文)").collect();
+    let found = |offset, found, expected| Mismatch {
+        offset,
+        found,
+        expected,
+    };
+    assert_eq!(
+        vec![
+            found(6, ',', '，'),
+            found(12, '，', ','),
+            found(20, '(', '（'),
+            found(27, ')', '）'),
+        ],
+        result
+    );
+    assert_eq!(
+        "'，' between Western parts, expected ','",
+        result[1].to_string()
+    );
+}
+
+#[test]
+fn test_linter() {
+    let options = Options::default();
+    let mut result = String::new();
+    result.push_txt("<Hyphenation", &options);
+    result.push_txt(">", &options);
+    assert_eq!("&lt;Hy\u{00AD}phen\u{00AD}ation&gt;", result);
+}
+
+#[test]
+fn test_stream() {
+    let options = Options::default();
+    let pieces = ["这是Hy", "phen", "ation的", "文字，", "example.", "com"];
+    let mut stream = Stream::new(String::new());
+    for piece in pieces.iter() {
+        stream.push_txt(piece, &options);
+    }
+    assert_eq!(
+        lint(&pieces.concat(), &options),
+        stream.into_inner().unwrap()
+    );
+
+    let mut stream = Stream::new(String::new());
+    stream.push_txt("中文", &options);
+    stream.push_raw("<em>");
+    stream.push_txt("Rust", &options);
+    stream.push_raw("</em>");
+    stream.reset();
+    stream.push_txt("中文", &options);
+    assert_eq!(
+        "中文<em>\u{2009}Rust</em>中文",
+        stream.into_inner().unwrap()
+    );
+}
+
+#[test]
+fn test_stream_io() {
+    let options = Options::default();
+    let mut stream = Stream::new(Io::new(Vec::new()));
+    stream.push_txt(">这是Hyphen", &options);
+    stream.push_txt("ation的文字", &options);
+    let bytes = stream.into_inner().unwrap().into_inner();
+    assert_eq!(
+        lint(">这是Hyphenation的文字", &options),
+        String::from_utf8(bytes).unwrap()
+    );
+}
@@ -0,0 +1,169 @@
+//! Local files posts refer to, copied into the site along with them.
+//!
+//! A relative image or link target with an extension, but `.html`, is an asset, resolved against
+//! the directory of the input file. Site-absolute paths are already under DESTROOT and left alone.
+//!
+//! ```plain
+//! Post    ![](img/a.png)  => /category/pagename/a.png       next to the post, by file name
+//! Hashed  ![](img/a.png)  => /assets/2c4f9a0d3e51b6a8.png   shared, by FNV-1a of the content
+//! ```
+//!
+//! # Example
+//!
+//! ```
+//! use asset::{local, target, Assets};
+//!
+//! let source = local(Path::new("posts"), "img/a.png").unwrap();
+//! let target = target(Assets::Post, &source, &["category", "pagename"]);
+//! ```
+use render::DESTROOT;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where assets are copied to.
+#[allow(dead_code)] // The one in use is chosen in config.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Assets {
+    /// The folder of the post citing it, by file name.
+    Post,
+    /// The `/assets/` folder, by hash of the content, so a file is copied once and can be cached
+    /// forever.
+    Hashed,
+}
+
+/// A local file and where it goes, relative to DESTROOT.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Asset {
+    pub source: PathBuf,
+    pub target: PathBuf,
+}
+
+impl Asset {
+    /// Returns the site-absolute URL of the target.
+    pub fn url(&self) -> String {
+        let mut url = String::new();
+        for component in self.target.iter() {
+            url.push('/');
+            url.push_str(&component.to_string_lossy());
+        }
+        url
+    }
+}
+
+/// Returns the file dest refers to, a site-absolute path is under DESTROOT, None for a URL.
+pub fn local(root: &Path, dest: &str) -> Option<PathBuf> {
+    let path = dest.split(['?', '#']).next().unwrap();
+    let scheme = path.split('/').next().unwrap().contains(':');
+    if path.is_empty() || scheme || path.starts_with("//") {
+        None
+    } else if let Some(path) = path.strip_prefix('/') {
+        Some(Path::new(DESTROOT).join(path))
+    } else {
+        Some(root.join(path))
+    }
+}
+
+/// Returns if dest is an asset rather than a page or a URL.
+pub fn is_asset(dest: &str) -> bool {
+    let path = dest.split(['?', '#']).next().unwrap();
+    match Path::new(path).extension().and_then(|x| x.to_str()) {
+        Some(ext) => {
+            !(path.starts_with('/')
+                || path.split('/').next().unwrap().contains(':')
+                || ext.eq_ignore_ascii_case("html")
+                || ext.eq_ignore_ascii_case("htm"))
+        }
+        None => false,
+    }
+}
+
+/// Returns where source goes relative to DESTROOT, dir is the folder of the post.
+pub fn target(assets: Assets, source: &Path, dir: &[&str]) -> PathBuf {
+    let name = source.file_name().unwrap_or_default();
+    match assets {
+        Assets::Post => dir.iter().collect::<PathBuf>().join(name),
+        Assets::Hashed => {
+            // A missing file is reported before anything is copied, any name does here.
+            let hash = fs::read(source).map_or(0, |x| fnv(&x));
+            let mut name = format!("{:016x}", hash);
+            if let Some(ext) = source.extension() {
+                name.push('.');
+                name.push_str(&ext.to_string_lossy());
+            }
+            Path::new("assets").join(name)
+        }
+    }
+}
+
+/// Copy the assets into DESTROOT.
+pub fn copy(assets: &[Asset]) {
+    for asset in assets {
+        let path = Path::new(DESTROOT).join(&asset.target);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::copy(&asset.source, path).unwrap();
+    }
+}
+
+/// 64-bit FNV-1a, stable across builds unlike the hasher of std.
+fn fnv(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fnv, is_asset, local, target, Asset, Assets};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_local() {
+        let cases = [
+            ("img/a.png", Some("posts/img/a.png")),
+            ("a.pdf?raw#page=2", Some("posts/a.pdf")),
+            ("/img/a.png", Some("public/img/a.png")),
+            ("https://a.com/a.png", None),
+            ("//a.com/a.png", None),
+            ("#top", None),
+        ];
+        for &(input, output) in cases.iter() {
+            let output = output.map(PathBuf::from);
+            assert_eq!(output, local(Path::new("posts"), input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_is_asset() {
+        let cases = [
+            ("img/a.png", true),
+            ("../a.PDF#page=2", true),
+            ("../rust/", false),
+            ("page.html", false),
+            ("/img/a.png", false),
+            ("https://a.com/a.png", false),
+            ("mailto:a@b.com", false),
+            ("#top", false),
+        ];
+        for &(input, output) in cases.iter() {
+            assert_eq!(output, is_asset(input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_target() {
+        let source = Path::new("posts/img/a.png");
+        let asset = Asset {
+            source: source.to_path_buf(),
+            target: target(Assets::Post, source, &["c", "p"]),
+        };
+        assert_eq!("/c/p/a.png", asset.url());
+        assert_eq!(
+            Path::new("assets/0000000000000000.png"),
+            target(Assets::Hashed, source, &["c", "p"])
+        );
+        assert_eq!(0xcbf2_9ce4_8422_2325, fnv(b""));
+        assert_eq!(0xa430_d846_80aa_bd0b, fnv(b"hello"));
+    }
+}
@@ -0,0 +1,292 @@
+//! Lint report for the source markdown.
+//!
+//! Walk the markdown events like Blog does, and report what the author had better fix by hand:
+//!
+//! ```plain
+//! space       => 中文 Rust     a space typed between CJK and Western parts, the linter inserts one
+//! punctuation => 中文,中文     punctuation of the wrong width, see linter::mismatches
+//! hyphenation => Hyphenatoin  a long word without any hyphenation point, likely a typo
+//! repeated    => the the      a Western word repeated
+//! trailing    => Rust␠        whitespace at the end of a line, except a hard break
+//! html        => <font>       raw HTML the policy drops, see sanitize
+//! unsafe      => <script>     scripts, event handlers and javascript: URLs, links too, see dangers
+//! footnote    => [^1]         a footnote cited but not defined, or defined but never cited
+//! math        => $\foo$       TeX math not supported by math::mathml, which stays TeX
+//! ```
+//!
+//! Code spans and blocks, including the metadata and front matter, and math are not checked but for
+//! trailing whitespace.
+//!
+//! # Example
+//!
+//! ```
+//! use check::check;
+//! use report::{write_all, Format};
+//!
+//! write_all(&mut stdout(), &[("ulysses.md", &check(&data))], Format::Text).unwrap();
+//! ```
+use config::{HTML, LINTER};
+use linter::{classify, edges, mismatches, Hyphenator, Scripts};
+use math::mathml;
+use parser::{markdown, title};
+use pulldown_cmark::{CowStr, Event, Tag, TagEnd};
+use report::Report;
+use sanitize::{unsafe_scheme, Sanitizer};
+use std::collections::HashSet;
+
+/// Words this long without any hyphenation point are reported.
+const UNKNOWN: usize = 10;
+
+/// Returns the byte offset of inner in outer, if inner is a slice of it.
+fn offset(outer: &str, inner: &str) -> Option<usize> {
+    let start = outer.as_ptr() as usize;
+    let ptr = inner.as_ptr() as usize;
+    if ptr >= start && ptr + inner.len() <= start + outer.len() {
+        Some(ptr - start)
+    } else {
+        None
+    }
+}
+
+/// Returns the findings in source.
+pub fn check<'a>(source: &'a str) -> Report<'a> {
+    let mut report = Report::new(source);
+    let mut hyphenator = Hyphenator::new();
+    let mut sanitizer = Sanitizer::new(&HTML);
+    let mut discard = String::new();
+    let mut code = 0;
+    // Footnotes defined in the post with their offsets, and the ones cited.
+    let mut defined = Vec::new();
+    let mut cited = HashSet::new();
+    // Offset of a `[` text, an undefined footnote is cited if `^name` follows.
+    let mut bracket = None;
+    for (event, range) in markdown(source).into_offset_iter() {
+        let last = bracket.take();
+        match event {
+            Event::Start(Tag::CodeBlock(_)) | Event::Start(Tag::MetadataBlock(_)) => code += 1,
+            Event::End(TagEnd::CodeBlock) | Event::End(TagEnd::MetadataBlock(_)) => code -= 1,
+            Event::Text(ref text) if code == 0 && text.as_ref() == "[" => {
+                bracket = Some(range.start)
+            }
+            Event::Text(ref text)
+                if last.is_some()
+                    && text.starts_with('^')
+                    && !text.contains(char::is_whitespace) =>
+            {
+                report.push(
+                    last.unwrap(),
+                    "footnote",
+                    format!("footnote [{}] is not defined", text),
+                );
+            }
+            Event::Text(ref text) if code == 0 => {
+                let base = offset(source, text).unwrap_or(range.start);
+                check_text(&mut report, &mut hyphenator, base, text);
+            }
+            Event::Html(ref html) | Event::InlineHtml(ref html) => {
+                let base = offset(source, html).unwrap_or(range.start);
+                for finding in sanitizer.clean(&mut discard, html) {
+                    let rule = if finding.danger { "unsafe" } else { "html" };
+                    report.push(base + finding.offset, rule, finding.message);
+                }
+                discard.clear();
+            }
+            Event::Start(Tag::Link { ref dest_url, .. })
+            | Event::Start(Tag::Image { ref dest_url, .. }) => {
+                let image = matches!(event, Event::Start(Tag::Image { .. }));
+                if let Some(scheme) = unsafe_scheme(dest_url, image) {
+                    let kind = if image { "image" } else { "link" };
+                    report.push(
+                        range.start,
+                        "unsafe",
+                        format!("{}: URL of {} is not allowed", scheme, kind),
+                    );
+                }
+            }
+            Event::InlineMath(ref tex) | Event::DisplayMath(ref tex) => {
+                let display = matches!(event, Event::DisplayMath(_));
+                if let Err(message) = mathml(tex, display) {
+                    report.push(range.start, "math", message);
+                }
+            }
+            Event::FootnoteReference(name) => {
+                cited.insert(name);
+            }
+            Event::Start(Tag::FootnoteDefinition(name)) => defined.push((name, range.start)),
+            ref event if title(event) => {
+                sanitizer.reset();
+                check_notes(&mut report, &mut defined, &mut cited);
+            }
+            _ => (),
+        }
+    }
+    check_notes(&mut report, &mut defined, &mut cited);
+    check_lines(&mut report, source);
+    report.sort();
+    report
+}
+
+/// Returns the findings in source a strict policy refuses to build with.
+pub fn dangers<'a>(source: &'a str) -> Report<'a> {
+    let mut report = check(source);
+    report.diagnostics.retain(|x| x.rule == "unsafe");
+    report
+}
+
+/// Check a piece of text, which starts at byte offset base of the source.
+fn check_text(report: &mut Report, hyphenator: &mut Hyphenator, base: usize, text: &str) {
+    let mut chars = text.char_indices().peekable();
+    let mut last = None;
+    while let Some((idx, ch)) = chars.next() {
+        if let (' ', Some(last), Some(&(_, next))) = (ch, last, chars.peek()) {
+            if LINTER.gap(edges(last).1, edges(next).0) {
+                report.push(
+                    base + idx,
+                    "space",
+                    "space typed between CJK and Western parts, the linter inserts one".to_string(),
+                );
+            }
+        }
+        last = Some(ch);
+    }
+
+    for mismatch in mismatches(text) {
+        report.push(base + mismatch.offset, "punctuation", mismatch.to_string());
+    }
+
+    let mut word: Option<&str> = None;
+    let mut spaced = false;
+    for (script, run) in classify(text) {
+        let idx = base + offset(text, run).unwrap();
+        match script {
+            Scripts::English => {
+                if spaced && word.is_some_and(|x| x.eq_ignore_ascii_case(run)) {
+                    report.push(idx, "repeated", format!("'{}' is repeated", run));
+                }
+                if run.len() >= UNKNOWN
+                    && !run.bytes().skip(1).any(|x| x.is_ascii_uppercase())
+                    && hyphenator.breaks(run).next().is_none()
+                {
+                    report.push(
+                        idx,
+                        "hyphenation",
+                        format!("'{}' has no hyphenation point, check its spelling", run),
+                    );
+                }
+                word = Some(run);
+                spaced = false;
+            }
+            Scripts::Unknown if run.chars().all(char::is_whitespace) => spaced = true,
+            _ => {
+                word = None;
+                spaced = false;
+            }
+        }
+    }
+}
+
+/// Report the footnotes of a post defined but never cited, and forget them.
+fn check_notes(
+    report: &mut Report,
+    defined: &mut Vec<(CowStr, usize)>,
+    cited: &mut HashSet<CowStr>,
+) {
+    for (name, offset) in defined.drain(..) {
+        if !cited.contains(&name) {
+            report.push(
+                offset,
+                "footnote",
+                format!("footnote [^{}] is never cited", name),
+            );
+        }
+    }
+    cited.clear();
+}
+
+/// Check the lines of source for trailing whitespace, two spaces for a hard break are fine.
+fn check_lines(report: &mut Report, source: &str) {
+    let mut start = 0;
+    for line in source.split('\n') {
+        let content = line.trim_end_matches('\r');
+        let trimmed = content.trim_end();
+        let trailing = &content[trimmed.len()..];
+        let hard_break = trailing == "  " && !trimmed.is_empty();
+        if !trailing.is_empty() && !hard_break {
+            report.push(
+                start + trimmed.len(),
+                "trailing",
+                "trailing whitespace".to_string(),
+            );
+        }
+        start += line.len() + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, dangers};
+
+    #[test]
+    fn test_check() {
+        let source = "# 标题\n\n    本文发表于：2018-01-01\n\n\
+                      中文 Rust,看看the the xxxxxxxxxx  \n\
+                      Rust\t\n\n\
+                      `中文 Rust,`\n\n\
+                      <p style=\"x\">\n\n\
+                      看<a onclick=\"x\">Rust</a>\n\n\
+                      注释[^1][^x]\n\n\
+                      [^1]: 注释\n\n\
+                      [^u]: 未引用\n\n\
+                      公式$x^2$和$\\foo$\n\n\
+                      [链接](javascript:alert(1))![图](data:image/png;base64,x)\n";
+        let result: Vec<_> = check(source)
+            .diagnostics
+            .into_iter()
+            .map(|x| (x.line, x.column, x.rule))
+            .collect();
+        assert_eq!(
+            vec![
+                (5, 3, "space"),
+                (5, 8, "punctuation"),
+                (5, 15, "repeated"),
+                (5, 19, "hyphenation"),
+                (6, 5, "trailing"),
+                (10, 1, "html"),
+                (12, 2, "unsafe"),
+                (14, 7, "footnote"),
+                (18, 1, "footnote"),
+                (20, 9, "math"),
+                (22, 1, "unsafe"),
+            ],
+            result
+        );
+    }
+
+    #[test]
+    fn test_dangers() {
+        let cases = [
+            ("[链接](/rust/)<kbd>Rust</kbd>", vec![]),
+            ("<font>Rust</font>", vec![]),
+            (
+                "[链接](javascript:alert(1))",
+                vec!["javascript: URL of link is not allowed"],
+            ),
+            (
+                "看![图](data:text/html,x)",
+                vec!["data: URL of image is not allowed"],
+            ),
+            (
+                "<a href=\"vbscript:x\">",
+                vec!["vbscript: URL of href is not allowed"],
+            ),
+        ];
+        for (input, output) in cases.iter() {
+            let result: Vec<_> = dangers(input)
+                .diagnostics
+                .into_iter()
+                .map(|x| x.message)
+                .collect();
+            assert_eq!(output, &result, "{}", input);
+        }
+    }
+}
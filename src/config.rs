@@ -0,0 +1,142 @@
+//! Compile-time configuration of mblog.
+//!
+//! Tweak the constants and rebuild.
+use asset::Assets;
+use linter::{Hyphens, Lang, Options, Punctuation, Spacing, Typography};
+use parser::{Links, Notes};
+use sanitize::Policy;
+
+/// Options used to lint every title and post. Keep Hyphens::Soft here, the output formats below
+/// rewrite soft hyphens in text on their own, attributes get none. Punctuation of the wrong width
+/// is reported by `mblog check` whatever is set here.
+pub const LINTER: Options = Options {
+    hyphens: Hyphens::Soft,
+    spacing: Spacing::Thin,
+    acronym: true,
+    camel_case: true,
+    identifier: true,
+    alphanumeric: true,
+    chinese: true,
+    kana: true,
+    hangul: true,
+    typography: Typography::None,
+    punctuation: Punctuation::Keep,
+};
+
+/// Options used to lint code spans and blocks, which keep their punctuation.
+pub const CODE: Options = Options {
+    typography: Typography::None,
+    punctuation: Punctuation::Keep,
+    ..LINTER
+};
+
+/// Base URL of the site, without the trailing slash. Links to it are internal.
+pub const SITE: &str = "https://darknode.in";
+
+/// Attributes of links within the site.
+pub const INTERNAL: Links = Links {
+    target: None,
+    rel: None,
+    nofollow: false,
+    class: None,
+};
+
+/// Attributes of links to other sites, opened in a new tab without telling them where from.
+pub const EXTERNAL: Links = Links {
+    target: Some("_blank"),
+    rel: Some("noopener noreferrer"),
+    nofollow: false,
+    class: Some("external"),
+};
+
+/// Language of the site, and of posts telling neither by metadata nor by their text.
+pub const LANG: Lang = Lang::Hans;
+
+/// Raw HTML allowed in posts. A strict policy refuses to build when there are scripts, event
+/// handlers or `javascript:` URLs, and reports where they are.
+pub const HTML: Policy = Policy {
+    tags: &[
+        "a",
+        "abbr",
+        "audio",
+        "b",
+        "blockquote",
+        "br",
+        "caption",
+        "cite",
+        "code",
+        "col",
+        "colgroup",
+        "dd",
+        "del",
+        "details",
+        "dfn",
+        "div",
+        "dl",
+        "dt",
+        "em",
+        "figcaption",
+        "figure",
+        "h2",
+        "h3",
+        "h4",
+        "h5",
+        "h6",
+        "hr",
+        "i",
+        "img",
+        "ins",
+        "kbd",
+        "li",
+        "mark",
+        "ol",
+        "p",
+        "picture",
+        "pre",
+        "q",
+        "rp",
+        "rt",
+        "ruby",
+        "s",
+        "samp",
+        "small",
+        "source",
+        "span",
+        "strong",
+        "sub",
+        "summary",
+        "sup",
+        "table",
+        "tbody",
+        "td",
+        "tfoot",
+        "th",
+        "thead",
+        "time",
+        "tr",
+        "u",
+        "ul",
+        "var",
+        "video",
+        "wbr",
+    ],
+    attributes: &[
+        "align", "alt", "class", "cite", "colspan", "controls", "datetime", "height", "href", "id",
+        "lang", "loop", "muted", "open", "poster", "rowspan", "src", "srcset", "start", "title",
+        "type", "width",
+    ],
+    schemes: &["http", "https", "mailto"],
+    strict: false,
+};
+
+/// Where footnotes go.
+pub const NOTES: Notes = Notes::End;
+
+/// Where local images and attachments of posts are copied to.
+pub const ASSETS: Assets = Assets::Post;
+
+/// Hyphens written into post pages.
+pub const PAGE_HYPHENS: Hyphens = Hyphens::Soft;
+
+/// Hyphens written into the Atom feed.
+pub const FEED_HYPHENS: Hyphens = Hyphens::Soft;
@@ -0,0 +1,170 @@
+//! Escaping for the contexts where the source ends up in HTML.
+//!
+//! Each context is a wrapper escaping its str when displayed, so it goes into `write!` and `wite!`
+//! without an intermediate String. Entities are the ones the linter writes.
+//!
+//! ```plain
+//! Text  <a> & b        => &lt;a&gt; &#38; b                  element content
+//! Attr  "a" & 'b'      => &#34;a&#34; &#38; &#39;b&#39;       quoted attribute value
+//! Url   /a b/"c"?d&e   => /a%20b/%22c%22?d&#38;e             href and src, inside an attribute
+//! Cdata a]]>b          => a]]]]><![CDATA[>b                  inside a CDATA section
+//! ```
+//!
+//! Url percent-encodes what is not allowed in a URL, keeping valid `%XX` escapes as they are, and
+//! then escapes for the attribute. It never changes which URL it is: callers neutralise the schemes
+//! running a script first, see sanitize::unsafe_scheme.
+//!
+//! # Example
+//!
+//! ```
+//! use escape::{Attr, Url};
+//!
+//! write!(w, "<a href=\"{}\" title=\"{}\">", Url(dest), Attr(title)).unwrap();
+//! ```
+use std::fmt;
+
+/// Escapes for element content.
+pub struct Text<'a>(pub &'a str);
+
+/// Escapes for a quoted attribute value.
+pub struct Attr<'a>(pub &'a str);
+
+/// Percent-encodes as URL, then escapes for a quoted attribute value.
+pub struct Url<'a>(pub &'a str);
+
+/// Splits the `]]>` ending a CDATA section across two sections.
+pub struct Cdata<'a>(pub &'a str);
+
+impl<'a> fmt::Display for Text<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        escape(f, self.0, false)
+    }
+}
+
+impl<'a> fmt::Display for Attr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        escape(f, self.0, true)
+    }
+}
+
+impl<'a> fmt::Display for Url<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bytes = self.0.as_bytes();
+        let mut last = 0;
+        for (idx, &byte) in bytes.iter().enumerate() {
+            let keep = match byte {
+                b'%' => bytes.len() > idx + 2 && hex(bytes[idx + 1]) && hex(bytes[idx + 2]),
+                b'&' | b'\'' => false,
+                _ => byte.is_ascii_alphanumeric() || b"!#$()*+,-./:;=?@[]_~".contains(&byte),
+            };
+            if keep {
+                continue;
+            }
+            // Bytes of a non-ASCII char are all encoded, so a non-empty slice is on boundaries.
+            if last < idx {
+                f.write_str(&self.0[last..idx])?;
+            }
+            match byte {
+                b'&' => f.write_str("&#38;")?,
+                b'\'' => f.write_str("&#39;")?,
+                _ => write!(f, "%{:02X}", byte)?,
+            }
+            last = idx + 1;
+        }
+        f.write_str(&self.0[last..])
+    }
+}
+
+impl<'a> fmt::Display for Cdata<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts = self.0.split("]]>");
+        f.write_str(parts.next().unwrap())?;
+        for part in parts {
+            f.write_str("]]]]><![CDATA[>")?;
+            f.write_str(part)?;
+        }
+        Ok(())
+    }
+}
+
+fn hex(byte: u8) -> bool {
+    byte.is_ascii_hexdigit()
+}
+
+fn escape(f: &mut fmt::Formatter, text: &str, quotes: bool) -> fmt::Result {
+    let mut last = 0;
+    for (idx, ch) in text.char_indices() {
+        let entity = match ch {
+            '"' if quotes => "&#34;",
+            '\'' if quotes => "&#39;",
+            '&' => "&#38;",
+            '<' => "&lt;",
+            '>' => "&gt;",
+            _ => continue,
+        };
+        f.write_str(&text[last..idx])?;
+        f.write_str(entity)?;
+        last = idx + 1;
+    }
+    f.write_str(&text[last..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Attr, Cdata, Text, Url};
+
+    #[test]
+    fn test_escape() {
+        let cases = [
+            ("plain 中文", "plain 中文", "plain 中文"),
+            ("<script>", "&lt;script&gt;", "&lt;script&gt;"),
+            ("\" onload=\"x", "\" onload=\"x", "&#34; onload=&#34;x"),
+            ("' onload='x", "' onload='x", "&#39; onload=&#39;x"),
+            ("&amp;", "&#38;amp;", "&#38;amp;"),
+            ("</textarea>", "&lt;/textarea&gt;", "&lt;/textarea&gt;"),
+            (
+                "a\"b'c<d>e&f",
+                "a\"b'c&lt;d&gt;e&#38;f",
+                "a&#34;b&#39;c&lt;d&gt;e&#38;f",
+            ),
+        ];
+        for &(input, text, attr) in cases.iter() {
+            assert_eq!(text, Text(input).to_string(), "{}", input);
+            assert_eq!(attr, Attr(input).to_string(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_url() {
+        let cases = [
+            ("/rust/", "/rust/"),
+            ("https://a.com/?q=1&r=2#x", "https://a.com/?q=1&#38;r=2#x"),
+            (
+                "/a\" onmouseover=\"alert(1)",
+                "/a%22%20onmouseover=%22alert(1)",
+            ),
+            ("/a' onmouseover='x", "/a&#39;%20onmouseover=&#39;x"),
+            (
+                "/a><script>alert(1)</script>",
+                "/a%3E%3Cscript%3Ealert(1)%3C/script%3E",
+            ),
+            ("/a%20b/%zz/%", "/a%20b/%25zz/%25"),
+            ("/中文", "/%E4%B8%AD%E6%96%87"),
+            ("/a\nb\tc\\d`e", "/a%0Ab%09c%5Cd%60e"),
+        ];
+        for &(input, output) in cases.iter() {
+            assert_eq!(output, Url(input).to_string(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_cdata() {
+        let cases = [
+            ("<p>a]]b</p>", "<p>a]]b</p>"),
+            ("a]]>b]]>", "a]]]]><![CDATA[>b]]]]><![CDATA[>"),
+        ];
+        for &(input, output) in cases.iter() {
+            assert_eq!(output, Cdata(input).to_string(), "{}", input);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Front matter of posts written for other generators, the subset of YAML and TOML they use.
+//!
+//! ```plain
+//! ---                           +++
+//! title: Rust                   title = "Rust"
+//! date: 2018-01-01T12:45:00Z    date = 2018-01-01T12:45:00Z
+//! tags: [a, b]                  tags = ["a", "b"]
+//! categories:                   categories = ["category"]
+//!   - category
+//! ---                           +++
+//! ```
+//!
+//! Top-level keys of a scalar or a list of scalars are read. Nested maps, tables and multi-line
+//! strings are skipped, and list items are split by commas, quoted or not.
+//!
+//! # Example
+//!
+//! ```
+//! use front::fields;
+//!
+//! for (key, values) in fields("title: Rust", MetadataBlockKind::YamlStyle) {
+//!     println!("{}: {:?}", key, values);
+//! }
+//! ```
+use pulldown_cmark::MetadataBlockKind;
+
+/// Returns the keys in text with their values, a scalar is a list of one.
+pub fn fields(text: &str, kind: MetadataBlockKind) -> Vec<(&str, Vec<String>)> {
+    let yaml = kind == MetadataBlockKind::YamlStyle;
+    let mut fields: Vec<(&str, Vec<String>)> = Vec::new();
+    // If the last key has no value, so a YAML block list may follow.
+    let mut open = false;
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if !yaml && trimmed.starts_with('[') {
+            // Keys after a table header belong to the table.
+            break;
+        }
+        if yaml && (trimmed == "-" || trimmed.starts_with("- ")) {
+            if let (true, Some(&mut (_, ref mut values))) = (open, fields.last_mut()) {
+                values.push(scalar(&trimmed[1..], yaml));
+            }
+            continue;
+        }
+        open = false;
+        if line.starts_with(char::is_whitespace) {
+            continue;
+        }
+        let (key, value) = match line.split_once(if yaml { ':' } else { '=' }) {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => continue,
+        };
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            continue;
+        }
+        let values = if value.is_empty() || value.starts_with('#') {
+            open = yaml;
+            Vec::new()
+        } else if let Some(list) = value.strip_prefix('[') {
+            let list = list.rsplit_once(']').map_or(list, |x| x.0);
+            list.split(',')
+                .map(|x| scalar(x, yaml))
+                .filter(|x| !x.is_empty())
+                .collect()
+        } else {
+            vec![scalar(value, yaml)]
+        };
+        fields.push((key, values));
+    }
+    fields
+}
+
+/// Returns the string a scalar stands for, unquoted and without a trailing comment.
+fn scalar(text: &str, yaml: bool) -> String {
+    let text = text.trim();
+    match text.chars().next() {
+        Some(quote @ '"') | Some(quote @ '\'') => {
+            let inner = text[1..].rsplit_once(quote).map_or(&text[1..], |x| x.0);
+            if quote == '"' {
+                inner.replace("\\\"", "\"").replace("\\\\", "\\")
+            } else if yaml {
+                inner.replace("''", "'")
+            } else {
+                inner.to_string()
+            }
+        }
+        _ => {
+            let comment = if yaml { " #" } else { "#" };
+            text.split(comment).next().unwrap().trim_end().to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fields;
+    use pulldown_cmark::MetadataBlockKind::{PlusesStyle, YamlStyle};
+
+    #[test]
+    fn test_fields() {
+        let yaml = "title: \"Rust \\\"2018\\\"\"\ndate: 2018-01-01 # local\n\
+                    tags: [a, 'b c']\ncategories:\n  - category\n- other\n\
+                    author:\n  name: Daniel\nslug: rust\n";
+        let toml = "title = 'Rust'\ndate = 2018-01-01T12:45:00+08:00\n\
+                    tags = [\"a\", \"b c\"] # two\n\n[author]\nname = \"Daniel\"\n";
+        let cases = [
+            (
+                fields(yaml, YamlStyle),
+                vec![
+                    ("title", vec!["Rust \"2018\""]),
+                    ("date", vec!["2018-01-01"]),
+                    ("tags", vec!["a", "b c"]),
+                    ("categories", vec!["category", "other"]),
+                    ("author", vec![]),
+                    ("slug", vec!["rust"]),
+                ],
+            ),
+            (
+                fields(toml, PlusesStyle),
+                vec![
+                    ("title", vec!["Rust"]),
+                    ("date", vec!["2018-01-01T12:45:00+08:00"]),
+                    ("tags", vec!["a", "b c"]),
+                ],
+            ),
+        ];
+        for (input, output) in cases.iter() {
+            let input: Vec<_> = input
+                .iter()
+                .map(|(key, values)| (*key, values.iter().map(String::as_str).collect()))
+                .collect();
+            assert_eq!(output, &input);
+        }
+    }
+}
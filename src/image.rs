@@ -0,0 +1,115 @@
+//! Intrinsic dimensions of local images, read from their headers.
+//!
+//! PNG, GIF, JPEG and WebP are understood, which is what posts use. Only the header is looked at,
+//! EXIF orientation is not.
+//!
+//! # Example
+//!
+//! ```
+//! use image::size;
+//!
+//! let (width, height) = size(Path::new("public/a.png")).unwrap();
+//! ```
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Bytes read from an image, enough for the headers but a JPEG with a large EXIF.
+const HEAD: u64 = 64 * 1024;
+
+/// Returns the width and height of the image at path, None if it is unreadable or unknown.
+pub fn size(path: &Path) -> Option<(u32, u32)> {
+    let mut bytes = Vec::new();
+    File::open(path)
+        .and_then(|f| f.take(HEAD).read_to_end(&mut bytes))
+        .ok()?;
+    dimensions(&bytes)
+}
+
+/// Returns the width and height in the header of an image.
+pub fn dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") && bytes.get(12..16)? == b"IHDR" {
+        Some((be32(bytes, 16)?, be32(bytes, 20)?))
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some((le16(bytes, 6)?, le16(bytes, 8)?))
+    } else if bytes.starts_with(b"\xFF\xD8") {
+        jpeg(bytes)
+    } else if bytes.starts_with(b"RIFF") && bytes.get(8..12)? == b"WEBP" {
+        webp(bytes)
+    } else {
+        None
+    }
+}
+
+fn be16(bytes: &[u8], idx: usize) -> Option<u32> {
+    let x = bytes.get(idx..idx + 2)?;
+    Some(u32::from(x[0]) << 8 | u32::from(x[1]))
+}
+
+fn be32(bytes: &[u8], idx: usize) -> Option<u32> {
+    Some(be16(bytes, idx)? << 16 | be16(bytes, idx + 2)?)
+}
+
+fn le16(bytes: &[u8], idx: usize) -> Option<u32> {
+    let x = bytes.get(idx..idx + 2)?;
+    Some(u32::from(x[1]) << 8 | u32::from(x[0]))
+}
+
+fn le24(bytes: &[u8], idx: usize) -> Option<u32> {
+    Some(le16(bytes, idx)? | u32::from(*bytes.get(idx + 2)?) << 16)
+}
+
+/// Walk the segments till a start of frame, which tells the size.
+fn jpeg(bytes: &[u8]) -> Option<(u32, u32)> {
+    let mut idx = 2;
+    loop {
+        if *bytes.get(idx)? != 0xFF {
+            return None;
+        }
+        let marker = *bytes.get(idx + 1)?;
+        match marker {
+            // Fill bytes.
+            0xFF => idx += 1,
+            // Markers without a length.
+            0x01 | 0xD0..=0xD7 => idx += 2,
+            0xC0..=0xCF if marker != 0xC4 && marker != 0xC8 && marker != 0xCC => {
+                return Some((be16(bytes, idx + 7)?, be16(bytes, idx + 5)?));
+            }
+            _ => idx += 2 + be16(bytes, idx + 2)? as usize,
+        }
+    }
+}
+
+/// The first chunk is lossy, lossless or extended.
+fn webp(bytes: &[u8]) -> Option<(u32, u32)> {
+    match bytes.get(12..16)? {
+        b"VP8 " => Some((le16(bytes, 26)? & 0x3FFF, le16(bytes, 28)? & 0x3FFF)),
+        b"VP8L" => {
+            let bits = le16(bytes, 21)? | le16(bytes, 23)? << 16;
+            Some(((bits & 0x3FFF) + 1, (bits >> 14 & 0x3FFF) + 1))
+        }
+        b"VP8X" => Some((le24(bytes, 24)? + 1, le24(bytes, 27)? + 1)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::dimensions;
+
+    #[test]
+    fn test_dimensions() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\x02\x80\0\0\x01\xe0\x08\x06";
+        let gif = b"GIF89a\x80\x02\xe0\x01\0";
+        // SOI, an APP0 of 4 bytes, then a SOF0 of 640x480.
+        let jpeg = b"\xff\xd8\xff\xe0\0\x04\0\0\xff\xc0\0\x11\x08\x01\xe0\x02\x80\x03";
+        let webp = b"RIFF\0\0\0\0WEBPVP8X\x0a\0\0\0\0\0\0\0\x7f\x02\0\xdf\x01\0";
+        let lossless = b"RIFF\0\0\0\0WEBPVP8L\0\0\0\0\x2f\x7f\xc2\x77\0";
+        for &input in [&png[..], gif, jpeg, webp, lossless].iter() {
+            assert_eq!(Some((640, 480)), dimensions(input), "{:?}", input);
+        }
+        for &input in [&png[..20], b"<svg>"].iter() {
+            assert_eq!(None, dimensions(input), "{:?}", input);
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Lang attributes for inline runs written in another language than the page.
+//!
+//! Browsers pick fonts and hyphenation patterns by `lang`, so runs of another script are wrapped
+//! in a span of their own:
+//!
+//! ```plain
+//! cmn-Hans page: 用Rust写  => 用<span lang="en">Rust</span>写
+//! cmn-Hans page: 日本語のかな => <span lang="ja">日本語のかな</span>
+//! en page:       Rust中文   => Rust<span lang="cmn-Hans">中文</span>
+//! ```
+//!
+//! A run starts and ends with a letter of its script, and takes in spaces, digits, punctuation
+//! and entities between them. Runs stop at tags and comments, contents of `code`, `script`, `style`
+//! and `math` are left alone. CJK runs are tagged by what they contain: Hangul for `ko`, Kana for `ja`,
+//! otherwise Chinese, which is never tagged in a Japanese page.
+//!
+//! # Example
+//!
+//! ```
+//! use lang::tag;
+//!
+//! let html = tag(&post.data, Lang::Hans);
+//! ```
+use linter::{markup, Lang, Scripts, Tally};
+
+/// Elements whose contents are kept as they are.
+const RAW: [&str; 4] = ["code", "script", "style", "math"];
+
+#[derive(Eq, PartialEq, Copy, Clone)]
+enum Unit {
+    /// A Western letter.
+    Western,
+    /// A CJK char.
+    Cjk,
+    /// Full-width punctuation, inside CJK runs only.
+    Wide,
+    /// Spaces, digits, punctuation and entities, inside any run.
+    Joint,
+    /// A tag, or anything else.
+    Stop,
+}
+
+/// Returns the Unit of the char or entity at the start of html, and its length.
+fn unit(html: &str) -> (Unit, usize) {
+    let ch = html.chars().next().unwrap();
+    if ch == '&' {
+        let name = html[1..]
+            .bytes()
+            .take(10)
+            .take_while(|x| x.is_ascii_alphanumeric() || *x == b'#')
+            .count();
+        if name > 0 && html[1 + name..].starts_with(';') {
+            return (Unit::Joint, name + 2);
+        }
+    }
+    let kind = match Scripts::from(ch) {
+        Scripts::English => Unit::Western,
+        Scripts::Chinese | Scripts::Kana | Scripts::Hangul => Unit::Cjk,
+        Scripts::Numbers => Unit::Joint,
+        Scripts::Unknown => match ch {
+            '<' => Unit::Stop,
+            '\u{3000}'..='\u{303F}' | '\u{FF00}'..='\u{FF65}' => Unit::Wide,
+            ' ' | '\u{00A0}' | '\u{00AD}' | '\u{2010}'..='\u{2027}' => Unit::Joint,
+            _ if ch.is_ascii_punctuation() => Unit::Joint,
+            _ => Unit::Stop,
+        },
+    };
+    (kind, ch.len_utf8())
+}
+
+/// Returns the Lang of a run, None if it needs no tagging in a page of lang.
+fn run_lang(run: &str, kind: Unit, lang: Lang) -> Option<Lang> {
+    let own = if kind == Unit::Western {
+        Lang::English
+    } else {
+        let mut tally = Tally::default();
+        tally.push(run);
+        match tally.lang() {
+            Some(Lang::Korean) => Lang::Korean,
+            Some(Lang::Japanese) => Lang::Japanese,
+            _ if lang == Lang::Japanese => return None,
+            _ if lang == Lang::Hans || lang == Lang::Hant => return None,
+            Some(Lang::Hant) => Lang::Hant,
+            _ => Lang::Hans,
+        }
+    };
+    if own == lang {
+        None
+    } else {
+        Some(own)
+    }
+}
+
+/// Returns html with the runs in another language than lang wrapped in spans.
+pub fn tag(html: &str, lang: Lang) -> String {
+    let mut result = String::with_capacity(html.len() + html.len() / 8);
+    let mut copied = 0;
+    let mut idx = 0;
+    // Start, end of the last letter and kind of the current run.
+    let mut run: Option<(usize, usize, Unit)> = None;
+    while idx < html.len() {
+        let (kind, len) = unit(&html[idx..]);
+        let joint =
+            kind == Unit::Joint || (kind == Unit::Wide && run.map(|x| x.2) == Some(Unit::Cjk));
+        match run {
+            Some((start, _, current)) if kind == current => run = Some((start, idx + len, current)),
+            Some(_) if joint => (),
+            Some((start, end, current)) => {
+                wrap(
+                    &mut result,
+                    &html[copied..start],
+                    &html[start..end],
+                    current,
+                    lang,
+                );
+                copied = end;
+                run = None;
+            }
+            None => (),
+        }
+        if run.is_none() && (kind == Unit::Western || kind == Unit::Cjk) {
+            run = Some((idx, idx + len, kind));
+        }
+        if kind == Unit::Stop && html[idx..].starts_with('<') {
+            idx += markup(&html[idx..], &RAW);
+            continue;
+        }
+        idx += len;
+    }
+    if let Some((start, end, current)) = run {
+        wrap(
+            &mut result,
+            &html[copied..start],
+            &html[start..end],
+            current,
+            lang,
+        );
+        copied = end;
+    }
+    result.push_str(&html[copied..]);
+    result
+}
+
+/// Push the text before a run, then the run, wrapped if it is in another language.
+fn wrap(result: &mut String, before: &str, run: &str, kind: Unit, lang: Lang) {
+    result.push_str(before);
+    match run_lang(run, kind, lang) {
+        Some(own) => {
+            result.push_str("<span lang=\"");
+            result.push_str(own.as_str());
+            result.push_str("\">");
+            result.push_str(run);
+            result.push_str("</span>");
+        }
+        None => result.push_str(run),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::tag;
+    use linter::Lang;
+
+    #[test]
+    fn test_tag() {
+        let cases = [
+            (Lang::Hans, "用Rust写", "用<span lang=\"en\">Rust</span>写"),
+            (
+                Lang::Hans,
+                "中文\u{2009}Liang&#39;s Algorithm.\u{2009}中文",
+                "中文\u{2009}<span lang=\"en\">Liang&#39;s Algorithm</span>.\u{2009}中文",
+            ),
+            (
+                Lang::Hans,
+                "<p>中文<em>Rust</em> and C</p>",
+                "<p>中文<em><span lang=\"en\">Rust</span></em> <span lang=\"en\">and C</span></p>",
+            ),
+            (
+                Lang::Hans,
+                "<a href=\"/rust/\">中文</a><code>let x</code>",
+                "<a href=\"/rust/\">中文</a><code>let x</code>",
+            ),
+            (
+                Lang::Hans,
+                "日本語のかな。",
+                "<span lang=\"ja\">日本語のかな</span>。",
+            ),
+            (Lang::Hans, "中文，中文", "中文，中文"),
+            (Lang::Japanese, "日本語と中文", "日本語と中文"),
+            (
+                Lang::Japanese,
+                "日本語とRust",
+                "日本語と<span lang=\"en\">Rust</span>",
+            ),
+            (
+                Lang::English,
+                "Rust 中文，這個 Rust",
+                "Rust <span lang=\"cmn-Hant\">中文，這個</span> Rust",
+            ),
+            (Lang::English, "Rust&amp;C 1", "Rust&amp;C 1"),
+            (
+                Lang::Hans,
+                "<div>&中文中文中文中文</div>",
+                "<div>&中文中文中文中文</div>",
+            ),
+            (
+                Lang::Hans,
+                "中文<abbr title=\"a>Rust b\">中文</abbr>",
+                "中文<abbr title=\"a>Rust b\">中文</abbr>",
+            ),
+            (
+                Lang::Hans,
+                "中文<!--post:rust's-->Rust",
+                "中文<!--post:rust's--><span lang=\"en\">Rust</span>",
+            ),
+        ];
+        for &(lang, input, output) in cases.iter() {
+            assert_eq!(output, tag(input, lang), "{}", input);
+        }
+    }
+}
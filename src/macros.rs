@@ -50,19 +50,3 @@ macro_rules! timer {
     };
 }
 
-/// Warper for ergonomic performance optimization
-///
-/// # Example
-///
-/// ```
-/// {
-///     unreachable!();
-/// }
-/// ```
-macro_rules! unreachable {
-    () => {
-        unsafe {
-            ::std::hint::unreachable_unchecked();
-        };
-    };
-}
@@ -1,16 +1,18 @@
 //! Yet another static site generator for who cares hyphenation in Western words and space between
 //! CJK and Western parts.
 //!
-//! mblog insert U+2009 between Chinese and Western parts across inline tags, insert U+00AD in the
+//! mblog insert U+2009 between CJK and Western parts across inline tags, insert U+00AD in the
 //! appropriate place inside Western words according to Liang's Hyphenation algorithm and LaTeX's
 //! corpus.
 //!
 //! # Usage
 //!
-//! Process file in arg, or data from stdin
+//! Process files and directories in args, or data from stdin. Each file holds one post or more, and
+//! directories are searched for `.md` files
 //!
 //! ```
 //! $ mblog ulysses.md
+//! $ mblog posts/ about.md
 //! ```
 //!
 //! or
@@ -19,8 +21,24 @@
 //! $ cat ulysses.md | mblog
 //! ```
 //!
+//! Report what the author had better fix by hand instead, as text or JSON
+//!
+//! ```
+//! $ mblog check ulysses.md
+//! $ mblog check --json posts/
+//! ```
+//!
 //! # Format
 //!
+//! The optional 语言 line sets the language of the post, otherwise it is detected from the
+//! scripts of the text. The optional 标签 line lists tags, which relate posts along with links and
+//! categories.
+//!
+//! Math between `$` or `$$` is rendered to MathML, see math.
+//!
+//! A file starting with YAML or TOML front matter holds one post, whose fields come from its keys,
+//! see front.
+//!
 //! Front matter and body are just plain markdown. Posts are joined with newline, which is the
 //! default format exported from [Ulysses](https://ulyssesapp.com).
 //!
@@ -30,7 +48,9 @@
 //!     本文发表于：2018-01-01T12:45:00+08:00
 //!     最后修改于：2018-01-12T06:15:00+08:00
 //!     分类：category
+//!     标签：tag-a、tag-b
 //!     地址：url-slug-a
+//!     语言：cmn-Hant
 //!
 //! ...
 //!
@@ -47,40 +67,151 @@
 #![recursion_limit = "128"]
 #[macro_use]
 extern crate fomat_macros;
+extern crate linter;
 extern crate pulldown_cmark;
 extern crate rayon;
 use std::alloc::System;
 use std::env;
-use std::fs::File;
-use std::io::{stdin, Read};
+use std::fs::{self, File};
+use std::io::{self, stderr, stdin, stdout, Read};
+use std::path::{Path, PathBuf};
+use std::process;
 
 #[global_allocator]
 static GLOBAL: System = System;
 
 #[macro_use]
 mod macros;
-mod linter;
+mod asset;
+mod check;
+mod config;
+mod escape;
+mod front;
+mod image;
+mod lang;
+mod math;
 mod parser;
 mod render;
+mod report;
+mod sanitize;
 
+use check::{check, dangers};
+use config::HTML;
 use parser::Blog;
 use render::Site;
+use report::{write_all, Format, Report};
 
-/// Process file in arg, or data from stdin
-fn main() {
-    timer!("total");
+/// Read file in path, or data from stdin for `-`
+fn read(path: &Path) -> io::Result<String> {
     let mut data = String::new();
 
-    match env::args().nth(1) {
-        Some(ref path) if path != "-" => {
-            File::open(path)
-                .and_then(|mut f| f.read_to_string(&mut data))
-                .unwrap();
+    if path != Path::new("-") {
+        File::open(path)?.read_to_string(&mut data)?;
+    } else {
+        stdin().read_to_string(&mut data)?;
+    }
+
+    Ok(data)
+}
+
+/// Returns the markdown files under path in order if it is a directory, or path itself. Symbolic
+/// links to directories under path are not followed.
+fn inputs(path: &Path) -> Result<Vec<PathBuf>, String> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut result = Vec::new();
+    let mut dirs = vec![path.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let error = |e: io::Error| format!("{}: {}", dir.display(), e);
+        for entry in fs::read_dir(&dir).map_err(error)? {
+            let entry = entry.map_err(error)?;
+            let kind = entry.file_type().map_err(error)?;
+            let path = entry.path();
+            let name = path.file_name().unwrap().to_string_lossy();
+            // A symbolic link to an ancestor would be walked forever.
+            if name.starts_with('.') || (kind.is_symlink() && path.is_dir()) {
+                continue;
+            }
+            if kind.is_dir() {
+                dirs.push(path);
+            } else if name.ends_with(".md") || name.ends_with(".markdown") {
+                result.push(path);
+            }
+        }
+    }
+    result.sort();
+    Ok(result)
+}
+
+/// Print message and exit as failed.
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+/// Process files and directories in args, or data from stdin
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let checking = args.first().map(String::as_str) == Some("check");
+    if checking {
+        args.remove(0);
+    }
+    let format = match args.iter().position(|x| x == "--json") {
+        Some(idx) => {
+            args.remove(idx);
+            Format::Json
         }
-        _ => {
-            stdin().read_to_string(&mut data).unwrap();
+        None => Format::Text,
+    };
+    let paths: Vec<PathBuf> = if args.is_empty() {
+        vec![PathBuf::from("-")]
+    } else {
+        let inputs: Result<Vec<_>, _> = args.iter().map(|x| inputs(Path::new(x))).collect();
+        inputs.unwrap_or_else(|x| fail(&x)).concat()
+    };
+    let names: Vec<String> = paths.iter().map(|x| x.display().to_string()).collect();
+    let data: Result<Vec<String>, String> = paths
+        .iter()
+        .zip(&names)
+        .map(|(path, name)| read(path).map_err(|e| format!("{}: {}", name, e)))
+        .collect();
+    let data = data.unwrap_or_else(|x| fail(&x));
+
+    if checking {
+        let reports: Vec<Report> = data.iter().map(|x| check(x)).collect();
+        let named: Vec<(&str, &Report)> = names.iter().map(String::as_str).zip(&reports).collect();
+        write_all(&mut stdout(), &named, format).unwrap();
+        let clean = reports.iter().all(|x| x.diagnostics.is_empty());
+        process::exit(if clean { 0 } else { 1 });
+    }
+
+    timer!("total");
+
+    if HTML.strict {
+        let reports: Vec<Report> = data.iter().map(|x| dangers(x)).collect();
+        if reports.iter().any(|x| !x.diagnostics.is_empty()) {
+            let named: Vec<(&str, &Report)> =
+                names.iter().map(String::as_str).zip(&reports).collect();
+            write_all(&mut stderr(), &named, Format::Text).unwrap();
+            process::exit(1);
         }
     }
 
-    Site::new(Blog::from(&data)).render();
+    let blog = paths
+        .iter()
+        .zip(&data)
+        .flat_map(|(path, data)| Blog::from(data).path(path));
+    let site = Site::new(blog);
+    for message in site.warnings() {
+        eprintln!("{}", message);
+    }
+    let errors = site.errors();
+    if !errors.is_empty() {
+        for message in errors {
+            eprintln!("{}", message);
+        }
+        process::exit(1);
+    }
+    site.render();
 }
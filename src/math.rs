@@ -0,0 +1,743 @@
+//! TeX math to MathML, rendered at build time without JavaScript.
+//!
+//! The subset of TeX in common use is understood:
+//!
+//! ```plain
+//! x_1^2 y' {ab}          scripts, primes and groups
+//! \alpha \Gamma \infty   Greek letters and symbols
+//! \leq \to \cdot \in     operators, relations and arrows
+//! \sum \int \lim \sin    large operators with limits in display math, and functions
+//! \frac \binom \sqrt     fractions, binomials and roots
+//! \hat \vec \overline    accents
+//! \left( \right)         fences
+//! \text \mathrm \mathbb  text, upright, bold, blackboard and calligraphic letters
+//! \, \; \quad ~          spaces
+//! \begin{pmatrix}        matrix, pmatrix, bmatrix, Bmatrix, vmatrix, Vmatrix, cases, aligned
+//! ```
+//!
+//! Anything else is an error naming what is not supported, and the caller falls back to the TeX.
+//! The TeX is kept as the annotation of the MathML, for copying.
+//!
+//! # Example
+//!
+//! ```
+//! use math::mathml;
+//!
+//! let math = mathml("x^2", false).unwrap();
+//! ```
+use escape::Text;
+use std::char;
+use std::fmt::Write;
+
+/// Letters and symbols written as identifiers.
+const IDENTIFIERS: [(&str, char); 50] = [
+    ("alpha", 'α'),
+    ("beta", 'β'),
+    ("gamma", 'γ'),
+    ("delta", 'δ'),
+    ("epsilon", 'ϵ'),
+    ("varepsilon", 'ε'),
+    ("zeta", 'ζ'),
+    ("eta", 'η'),
+    ("theta", 'θ'),
+    ("vartheta", 'ϑ'),
+    ("iota", 'ι'),
+    ("kappa", 'κ'),
+    ("lambda", 'λ'),
+    ("mu", 'μ'),
+    ("nu", 'ν'),
+    ("xi", 'ξ'),
+    ("pi", 'π'),
+    ("varpi", 'ϖ'),
+    ("rho", 'ρ'),
+    ("varrho", 'ϱ'),
+    ("sigma", 'σ'),
+    ("varsigma", 'ς'),
+    ("tau", 'τ'),
+    ("upsilon", 'υ'),
+    ("phi", 'ϕ'),
+    ("varphi", 'φ'),
+    ("chi", 'χ'),
+    ("psi", 'ψ'),
+    ("omega", 'ω'),
+    ("Gamma", 'Γ'),
+    ("Delta", 'Δ'),
+    ("Theta", 'Θ'),
+    ("Lambda", 'Λ'),
+    ("Xi", 'Ξ'),
+    ("Pi", 'Π'),
+    ("Sigma", 'Σ'),
+    ("Upsilon", 'Υ'),
+    ("Phi", 'Φ'),
+    ("Psi", 'Ψ'),
+    ("Omega", 'Ω'),
+    ("infty", '∞'),
+    ("partial", '∂'),
+    ("nabla", '∇'),
+    ("emptyset", '∅'),
+    ("varnothing", '∅'),
+    ("hbar", 'ℏ'),
+    ("ell", 'ℓ'),
+    ("aleph", 'ℵ'),
+    ("Re", 'ℜ'),
+    ("Im", 'ℑ'),
+];
+
+/// Symbols written as operators.
+const OPERATORS: [(&str, char); 88] = [
+    ("pm", '±'),
+    ("mp", '∓'),
+    ("times", '×'),
+    ("div", '÷'),
+    ("cdot", '⋅'),
+    ("ast", '∗'),
+    ("star", '⋆'),
+    ("circ", '∘'),
+    ("bullet", '∙'),
+    ("leq", '≤'),
+    ("le", '≤'),
+    ("geq", '≥'),
+    ("ge", '≥'),
+    ("neq", '≠'),
+    ("ne", '≠'),
+    ("approx", '≈'),
+    ("equiv", '≡'),
+    ("sim", '∼'),
+    ("simeq", '≃'),
+    ("cong", '≅'),
+    ("propto", '∝'),
+    ("ll", '≪'),
+    ("gg", '≫'),
+    ("in", '∈'),
+    ("notin", '∉'),
+    ("ni", '∋'),
+    ("subset", '⊂'),
+    ("subseteq", '⊆'),
+    ("supset", '⊃'),
+    ("supseteq", '⊇'),
+    ("cup", '∪'),
+    ("cap", '∩'),
+    ("setminus", '∖'),
+    ("wedge", '∧'),
+    ("land", '∧'),
+    ("vee", '∨'),
+    ("lor", '∨'),
+    ("neg", '¬'),
+    ("lnot", '¬'),
+    ("oplus", '⊕'),
+    ("otimes", '⊗'),
+    ("to", '→'),
+    ("rightarrow", '→'),
+    ("leftarrow", '←'),
+    ("gets", '←'),
+    ("leftrightarrow", '↔'),
+    ("Rightarrow", '⇒'),
+    ("Leftarrow", '⇐'),
+    ("Leftrightarrow", '⇔'),
+    ("implies", '⟹'),
+    ("iff", '⟺'),
+    ("mapsto", '↦'),
+    ("uparrow", '↑'),
+    ("downarrow", '↓'),
+    ("forall", '∀'),
+    ("exists", '∃'),
+    ("ldots", '…'),
+    ("dots", '…'),
+    ("cdots", '⋯'),
+    ("vdots", '⋮'),
+    ("ddots", '⋱'),
+    ("mid", '∣'),
+    ("parallel", '∥'),
+    ("perp", '⊥'),
+    ("angle", '∠'),
+    ("prime", '′'),
+    ("colon", ':'),
+    ("langle", '⟨'),
+    ("rangle", '⟩'),
+    ("lfloor", '⌊'),
+    ("rfloor", '⌋'),
+    ("lceil", '⌈'),
+    ("rceil", '⌉'),
+    ("vert", '|'),
+    ("lvert", '|'),
+    ("rvert", '|'),
+    ("Vert", '‖'),
+    ("|", '‖'),
+    ("{", '{'),
+    ("}", '}'),
+    ("lbrace", '{'),
+    ("rbrace", '}'),
+    ("%", '%'),
+    ("$", '$'),
+    ("#", '#'),
+    ("&", '&'),
+    ("_", '_'),
+    ("backslash", '\\'),
+];
+
+/// Large operators, the ones before the integrals take limits in display math.
+const LARGE: [(&str, char); 14] = [
+    ("sum", '∑'),
+    ("prod", '∏'),
+    ("coprod", '∐'),
+    ("bigcup", '⋃'),
+    ("bigcap", '⋂'),
+    ("bigoplus", '⨁'),
+    ("bigotimes", '⨂'),
+    ("bigvee", '⋁'),
+    ("bigwedge", '⋀'),
+    ("int", '∫'),
+    ("iint", '∬'),
+    ("iiint", '∭'),
+    ("oint", '∮'),
+    ("smallint", '∫'),
+];
+
+/// Functions written upright, the ones from lim take limits in display math.
+const FUNCTIONS: [&str; 30] = [
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "log", "ln", "lg", "exp", "deg", "dim", "ker", "hom", "arg", "lim", "liminf", "limsup", "max",
+    "min", "sup", "inf", "det", "gcd",
+];
+
+/// Accents over, or under for underline, their argument.
+const ACCENTS: [(&str, char); 9] = [
+    ("hat", '^'),
+    ("widehat", '^'),
+    ("bar", '¯'),
+    ("overline", '‾'),
+    ("vec", '→'),
+    ("dot", '˙'),
+    ("ddot", '¨'),
+    ("tilde", '~'),
+    ("underline", '_'),
+];
+
+/// Spaces and their widths in em.
+const SPACES: [(&str, &str); 7] = [
+    (",", "0.1667em"),
+    (":", "0.2222em"),
+    (">", "0.2222em"),
+    (";", "0.2778em"),
+    ("!", "-0.1667em"),
+    ("quad", "1em"),
+    ("qquad", "2em"),
+];
+
+/// Environments and their fences.
+const ENVIRONMENTS: [(&str, &str, &str); 8] = [
+    ("matrix", "", ""),
+    ("pmatrix", "(", ")"),
+    ("bmatrix", "[", "]"),
+    ("Bmatrix", "{", "}"),
+    ("vmatrix", "|", "|"),
+    ("Vmatrix", "‖", "‖"),
+    ("cases", "{", ""),
+    ("aligned", "", ""),
+];
+
+/// Letters encoded before the alphabets, which leave holes for them.
+const HOLES: [(&str, char, char); 15] = [
+    ("mathbb", 'C', 'ℂ'),
+    ("mathbb", 'H', 'ℍ'),
+    ("mathbb", 'N', 'ℕ'),
+    ("mathbb", 'P', 'ℙ'),
+    ("mathbb", 'Q', 'ℚ'),
+    ("mathbb", 'R', 'ℝ'),
+    ("mathbb", 'Z', 'ℤ'),
+    ("mathcal", 'B', 'ℬ'),
+    ("mathcal", 'E', 'ℰ'),
+    ("mathcal", 'F', 'ℱ'),
+    ("mathcal", 'H', 'ℋ'),
+    ("mathcal", 'I', 'ℐ'),
+    ("mathcal", 'L', 'ℒ'),
+    ("mathcal", 'M', 'ℳ'),
+    ("mathcal", 'R', 'ℛ'),
+];
+
+fn find<T: Copy>(table: &[(&str, T)], name: &str) -> Option<T> {
+    table.iter().find(|x| x.0 == name).map(|x| x.1)
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+enum Token<'a> {
+    /// A command, without the backslash.
+    Command(&'a str),
+    Letter(char),
+    Number(&'a str),
+    Symbol(char),
+}
+
+/// What ends a row.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+enum End {
+    Eof,
+    Brace,
+    Bracket,
+    Right,
+    Cell,
+}
+
+/// Returns the MathML of tex, or what it does not support.
+pub fn mathml(tex: &str, display: bool) -> Result<String, String> {
+    let mut parser = Parser {
+        tex,
+        idx: 0,
+        display,
+    };
+    let row = parser.row(End::Eof)?;
+    let mut result = String::with_capacity(tex.len() * 4 + 96);
+    result.push_str(if display {
+        "<math display=\"block\">"
+    } else {
+        "<math>"
+    });
+    write!(
+        &mut result,
+        "<semantics>{}<annotation encoding=\"application/x-tex\">{}</annotation></semantics></math>",
+        mrow(row),
+        Text(tex)
+    )
+    .unwrap();
+    Ok(result)
+}
+
+/// Returns the elements as one.
+fn mrow(row: Vec<String>) -> String {
+    if row.len() == 1 {
+        row.into_iter().next().unwrap()
+    } else {
+        format!("<mrow>{}</mrow>", row.concat())
+    }
+}
+
+/// Returns ch in the alphabet of style, None if it has no such letter.
+fn alphabet(style: &str, ch: char) -> Option<char> {
+    if let Some(x) = HOLES.iter().find(|x| x.0 == style && x.1 == ch) {
+        return Some(x.2);
+    }
+    // Where the capitals, small letters and digits start.
+    let (upper, lower, digit) = match style {
+        "mathbf" => (0x1D400, Some(0x1D41A), Some(0x1D7CE)),
+        "mathbb" => (0x1D538, Some(0x1D552), Some(0x1D7D8)),
+        "mathcal" => (0x1D49C, None, None),
+        _ => return None,
+    };
+    let code = match ch {
+        'A'..='Z' => upper + (ch as u32 - 'A' as u32),
+        'a'..='z' => lower? + (ch as u32 - 'a' as u32),
+        '0'..='9' => digit? + (ch as u32 - '0' as u32),
+        _ => return None,
+    };
+    char::from_u32(code)
+}
+
+struct Parser<'a> {
+    tex: &'a str,
+    idx: usize,
+    display: bool,
+}
+
+impl<'a> Parser<'a> {
+    fn token(&mut self) -> Option<Token<'a>> {
+        let rest = self.tex[self.idx..].trim_start();
+        self.idx = self.tex.len() - rest.len();
+        let mut chars = rest.chars();
+        let ch = chars.next()?;
+        let next = chars.next();
+        let (token, len) = if ch == '\\' {
+            let letters = rest[1..]
+                .bytes()
+                .take_while(u8::is_ascii_alphabetic)
+                .count();
+            let len = if letters > 0 {
+                letters
+            } else {
+                next.map_or(0, char::len_utf8)
+            };
+            (Token::Command(&rest[1..1 + len]), 1 + len)
+        } else if ch.is_ascii_digit() || (ch == '.' && next.is_some_and(|x| x.is_ascii_digit())) {
+            let len = rest
+                .bytes()
+                .take_while(|x| x.is_ascii_digit() || *x == b'.')
+                .count();
+            (Token::Number(&rest[..len]), len)
+        } else if ch.is_alphabetic() {
+            (Token::Letter(ch), ch.len_utf8())
+        } else {
+            (Token::Symbol(ch), ch.len_utf8())
+        };
+        self.idx += len;
+        Some(token)
+    }
+
+    fn peek(&mut self) -> Option<Token<'a>> {
+        let idx = self.idx;
+        let token = self.token();
+        self.idx = idx;
+        token
+    }
+
+    /// Returns the elements till end, which is consumed but for `\right` and cells.
+    fn row(&mut self, end: End) -> Result<Vec<String>, String> {
+        let mut row = Vec::new();
+        loop {
+            match self.peek() {
+                None if end == End::Eof => return Ok(row),
+                None => return Err("unbalanced group".to_string()),
+                Some(Token::Symbol('}')) if end == End::Brace => break,
+                Some(Token::Symbol(']')) if end == End::Bracket => break,
+                Some(Token::Command("right")) if end == End::Right => return Ok(row),
+                Some(Token::Symbol('&'))
+                | Some(Token::Command("\\"))
+                | Some(Token::Command("end"))
+                    if end == End::Cell =>
+                {
+                    return Ok(row)
+                }
+                _ => row.push(self.element()?),
+            }
+        }
+        self.token();
+        Ok(row)
+    }
+
+    /// Returns an atom with its scripts.
+    fn element(&mut self) -> Result<String, String> {
+        let (base, limits) = match self.peek() {
+            Some(Token::Symbol('^')) | Some(Token::Symbol('_')) => {
+                ("<mrow></mrow>".to_string(), false)
+            }
+            _ => self.atom()?,
+        };
+        let mut sub = None;
+        let mut sup = None;
+        loop {
+            match self.peek() {
+                Some(Token::Symbol('_')) if sub.is_none() => {
+                    self.token();
+                    sub = Some(self.atom()?.0);
+                }
+                Some(Token::Symbol('^')) if sup.is_none() => {
+                    self.token();
+                    sup = Some(self.atom()?.0);
+                }
+                Some(Token::Symbol('\'')) if sup.is_none() => {
+                    let mut primes = String::new();
+                    while self.peek() == Some(Token::Symbol('\'')) {
+                        self.token();
+                        primes.push('′');
+                    }
+                    sup = Some(format!("<mo>{}</mo>", primes));
+                }
+                _ => break,
+            }
+        }
+        Ok(match (sub, sup) {
+            (None, None) => base,
+            (Some(sub), None) if limits => format!("<munder>{}{}</munder>", base, sub),
+            (Some(sub), None) => format!("<msub>{}{}</msub>", base, sub),
+            (None, Some(sup)) if limits => format!("<mover>{}{}</mover>", base, sup),
+            (None, Some(sup)) => format!("<msup>{}{}</msup>", base, sup),
+            (Some(sub), Some(sup)) if limits => {
+                format!("<munderover>{}{}{}</munderover>", base, sub, sup)
+            }
+            (Some(sub), Some(sup)) => format!("<msubsup>{}{}{}</msubsup>", base, sub, sup),
+        })
+    }
+
+    /// Returns an element without scripts, and whether it takes limits.
+    fn atom(&mut self) -> Result<(String, bool), String> {
+        let token = self.token().ok_or("missing argument")?;
+        let result = match token {
+            Token::Letter(ch) if ch.is_uppercase() && !ch.is_ascii() => {
+                format!("<mi mathvariant=\"normal\">{}</mi>", ch)
+            }
+            Token::Letter(ch) => format!("<mi>{}</mi>", ch),
+            Token::Number(number) => format!("<mn>{}</mn>", number),
+            Token::Symbol('{') => mrow(self.row(End::Brace)?),
+            Token::Symbol(ch @ '}')
+            | Token::Symbol(ch @ '&')
+            | Token::Symbol(ch @ '^')
+            | Token::Symbol(ch @ '_') => return Err(format!("unexpected {}", ch)),
+            Token::Symbol('~') => "<mtext>&#160;</mtext>".to_string(),
+            Token::Symbol('\'') => "<mo>′</mo>".to_string(),
+            Token::Symbol('-') => "<mo>−</mo>".to_string(),
+            Token::Symbol('*') => "<mo>∗</mo>".to_string(),
+            Token::Symbol(ch) => format!("<mo>{}</mo>", Text(ch.encode_utf8(&mut [0; 4]))),
+            Token::Command(name) => return self.command(name),
+        };
+        Ok((result, false))
+    }
+
+    fn command(&mut self, name: &'a str) -> Result<(String, bool), String> {
+        if let Some(ch) = find(&IDENTIFIERS, name) {
+            let atom = if ch.is_uppercase() {
+                format!("<mi mathvariant=\"normal\">{}</mi>", ch)
+            } else {
+                format!("<mi>{}</mi>", ch)
+            };
+            return Ok((atom, false));
+        }
+        if let Some(ch) = find(&OPERATORS, name) {
+            return Ok((
+                format!("<mo>{}</mo>", Text(ch.encode_utf8(&mut [0; 4]))),
+                false,
+            ));
+        }
+        if let Some(idx) = LARGE.iter().position(|x| x.0 == name) {
+            let limits = self.display && idx < 9;
+            return Ok((
+                format!("<mo largeop=\"true\">{}</mo>", LARGE[idx].1),
+                limits,
+            ));
+        }
+        if let Some(idx) = FUNCTIONS.iter().position(|&x| x == name) {
+            let limits = self.display && idx >= 21;
+            return Ok((format!("<mi>{}</mi>", name), limits));
+        }
+        if let Some(width) = find(&SPACES, name) {
+            return Ok((format!("<mspace width=\"{}\"></mspace>", width), false));
+        }
+        if let Some(accent) = find(&ACCENTS, name) {
+            let (base, _) = self.atom()?;
+            let result = if name == "underline" {
+                format!(
+                    "<munder accentunder=\"true\">{}<mo>{}</mo></munder>",
+                    base, accent
+                )
+            } else {
+                format!("<mover accent=\"true\">{}<mo>{}</mo></mover>", base, accent)
+            };
+            return Ok((result, false));
+        }
+        let result = match name {
+            "frac" | "dfrac" | "tfrac" => {
+                let (numerator, _) = self.atom()?;
+                let (denominator, _) = self.atom()?;
+                format!("<mfrac>{}{}</mfrac>", numerator, denominator)
+            }
+            "binom" => {
+                let (n, _) = self.atom()?;
+                let (k, _) = self.atom()?;
+                format!(
+                    "<mrow><mo>(</mo><mfrac linethickness=\"0\">{}{}</mfrac><mo>)</mo></mrow>",
+                    n, k
+                )
+            }
+            "sqrt" if self.peek() == Some(Token::Symbol('[')) => {
+                self.token();
+                let index = mrow(self.row(End::Bracket)?);
+                let (base, _) = self.atom()?;
+                format!("<mroot>{}{}</mroot>", base, index)
+            }
+            "sqrt" => format!("<msqrt>{}</msqrt>", self.atom()?.0),
+            "text" | "textrm" | "mbox" => format!("<mtext>{}</mtext>", Text(self.raw()?)),
+            "mathrm" | "operatorname" => {
+                format!("<mi mathvariant=\"normal\">{}</mi>", Text(self.raw()?))
+            }
+            "mathbf" | "mathbb" | "mathcal" => {
+                let mut letters = String::new();
+                for ch in self.raw()?.chars().filter(|x| !x.is_whitespace()) {
+                    match alphabet(name, ch) {
+                        Some(ch) => letters.push(ch),
+                        None => return Err(format!("\\{} of {} is not supported", name, ch)),
+                    }
+                }
+                format!("<mi>{}</mi>", letters)
+            }
+            "left" => {
+                let open = self.fence()?;
+                let row = self.row(End::Right)?.concat();
+                self.token();
+                let close = self.fence()?;
+                format!("<mrow>{}{}{}</mrow>", open, row, close)
+            }
+            "begin" => self.environment()?,
+            " " => "<mtext>&#160;</mtext>".to_string(),
+            _ => return Err(format!("\\{} is not supported", name)),
+        };
+        Ok((result, false))
+    }
+
+    /// Returns the content of a group, which must follow, as it is.
+    fn raw(&mut self) -> Result<&'a str, String> {
+        if self.token() != Some(Token::Symbol('{')) {
+            return Err("missing {".to_string());
+        }
+        let start = self.idx;
+        let mut nest = 0;
+        for (idx, ch) in self.tex[start..].char_indices() {
+            match ch {
+                '{' => nest += 1,
+                '}' if nest == 0 => {
+                    self.idx = start + idx + 1;
+                    return Ok(&self.tex[start..start + idx]);
+                }
+                '}' => nest -= 1,
+                _ => (),
+            }
+        }
+        Err("unbalanced group".to_string())
+    }
+
+    /// Returns the fence after `\left` or `\right`.
+    fn fence(&mut self) -> Result<String, String> {
+        let fence = match self.token() {
+            Some(Token::Symbol('.')) => return Ok(String::new()),
+            Some(Token::Symbol(ch)) if "()[]|/".contains(ch) => ch,
+            Some(Token::Command(name)) => match find(&OPERATORS, name) {
+                Some(ch) if "{}⟨⟩⌊⌋⌈⌉|‖".contains(ch) => ch,
+                _ => return Err(format!("\\{} is not a fence", name)),
+            },
+            _ => return Err("missing fence".to_string()),
+        };
+        Ok(format!("<mo fence=\"true\">{}</mo>", fence))
+    }
+
+    /// Returns the environment after `\begin`, as a table between its fences.
+    fn environment(&mut self) -> Result<String, String> {
+        let name = self.raw()?;
+        let (open, close) = match ENVIRONMENTS.iter().find(|x| x.0 == name) {
+            Some(&(_, open, close)) => (open, close),
+            None => return Err(format!("environment {} is not supported", name)),
+        };
+        let mut rows = Vec::new();
+        let mut cells = Vec::new();
+        loop {
+            cells.push(mrow(self.row(End::Cell)?));
+            match self.token() {
+                Some(Token::Symbol('&')) => (),
+                Some(Token::Command("\\")) => rows.push(cells.split_off(0)),
+                _ => {
+                    if self.raw()? != name {
+                        return Err(format!("environment {} is not ended", name));
+                    }
+                    break;
+                }
+            }
+        }
+        // A trailing `\\` leaves an empty row.
+        if cells.len() > 1 || cells.first().is_some_and(|x| x != "<mrow></mrow>") {
+            rows.push(cells);
+        }
+        let mut table = String::from(match name {
+            "cases" => "<mtable columnalign=\"left left\">",
+            "aligned" => "<mtable columnalign=\"right left\">",
+            _ => "<mtable>",
+        });
+        for row in rows {
+            table.push_str("<mtr>");
+            for cell in row {
+                write!(&mut table, "<mtd>{}</mtd>", cell).unwrap();
+            }
+            table.push_str("</mtr>");
+        }
+        table.push_str("</mtable>");
+        let fence = |x: &str| {
+            if x.is_empty() {
+                String::new()
+            } else {
+                format!("<mo fence=\"true\">{}</mo>", x)
+            }
+        };
+        Ok(format!(
+            "<mrow>{}{}{}</mrow>",
+            fence(open),
+            table,
+            fence(close)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mathml;
+
+    /// Returns the MathML without the math and annotation around it.
+    fn inner(tex: &str, display: bool) -> Result<String, String> {
+        mathml(tex, display).map(|x| {
+            let start = x.find("<semantics>").unwrap() + 11;
+            let end = x.find("<annotation").unwrap();
+            x[start..end].to_string()
+        })
+    }
+
+    #[test]
+    fn test_mathml() {
+        let cases = [
+            ("x", "<mi>x</mi>"),
+            ("x^2", "<msup><mi>x</mi><mn>2</mn></msup>"),
+            (
+                "x_{i}^{2n}",
+                "<msubsup><mi>x</mi><mi>i</mi><mrow><mn>2</mn><mi>n</mi></mrow></msubsup>",
+            ),
+            ("f'", "<msup><mi>f</mi><mo>′</mo></msup>"),
+            ("a - b", "<mrow><mi>a</mi><mo>−</mo><mi>b</mi></mrow>"),
+            ("a<b", "<mrow><mi>a</mi><mo>&lt;</mo><mi>b</mi></mrow>"),
+            (
+                "\\alpha\\Gamma",
+                "<mrow><mi>α</mi><mi mathvariant=\"normal\">Γ</mi></mrow>",
+            ),
+            ("\\frac{1}{2}", "<mfrac><mn>1</mn><mn>2</mn></mfrac>"),
+            ("\\sqrt[3]{x}", "<mroot><mi>x</mi><mn>3</mn></mroot>"),
+            ("\\sqrt x", "<msqrt><mi>x</mi></msqrt>"),
+            (
+                "\\sum_{i=1}^n",
+                "<msubsup><mo largeop=\"true\">∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow>\
+                 <mi>n</mi></msubsup>",
+            ),
+            (
+                "\\left(x\\right.",
+                "<mrow><mo fence=\"true\">(</mo><mi>x</mi></mrow>",
+            ),
+            (
+                "\\hat x",
+                "<mover accent=\"true\"><mi>x</mi><mo>^</mo></mover>",
+            ),
+            ("\\mathbb{R}", "<mi>ℝ</mi>"),
+            ("\\text{if }x", "<mrow><mtext>if </mtext><mi>x</mi></mrow>"),
+            (
+                "\\begin{pmatrix}a&b\\\\c&d\\end{pmatrix}",
+                "<mrow><mo fence=\"true\">(</mo><mtable><mtr><mtd><mi>a</mi></mtd><mtd><mi>b</mi>\
+                 </mtd></mtr><mtr><mtd><mi>c</mi></mtd><mtd><mi>d</mi></mtd></mtr></mtable>\
+                 <mo fence=\"true\">)</mo></mrow>",
+            ),
+        ];
+        for &(input, output) in cases.iter() {
+            assert_eq!(Ok(output.to_string()), inner(input, false), "{}", input);
+        }
+        assert_eq!(
+            Ok(
+                "<munderover><mo largeop=\"true\">∑</mo><mi>i</mi><mi>n</mi></munderover>"
+                    .to_string()
+            ),
+            inner("\\sum_i^n", true)
+        );
+        assert_eq!(
+            "<math display=\"block\"><semantics><mi>x</mi>\
+             <annotation encoding=\"application/x-tex\">x</annotation></semantics></math>",
+            mathml("x", true).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let cases = [
+            ("\\foo", "\\foo is not supported"),
+            ("{x", "unbalanced group"),
+            ("x}", "unexpected }"),
+            ("x^", "missing argument"),
+            ("x^^2", "unexpected ^"),
+            (
+                "\\begin{array}x\\end{array}",
+                "environment array is not supported",
+            ),
+            ("\\mathcal{x}", "\\mathcal of x is not supported"),
+            ("\\left(x", "unbalanced group"),
+        ];
+        for &(input, output) in cases.iter() {
+            assert_eq!(Err(output.to_string()), mathml(input, false), "{}", input);
+        }
+    }
+}
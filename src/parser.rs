@@ -1,6 +1,7 @@
 //! Parser for Ulysses exported MarkDown bundle.
 //!
 //! Extract Post from a MarkDown bundle by iterating Blog.
+//! A file starting with front matter holds a single post instead, level 1 headings and all.
 //!
 //! # Example
 //!
@@ -12,11 +13,40 @@
 //!     println("{}", post.title);
 //! }
 //! ```
-use linter::{Linter, Scripts};
-use pulldown_cmark::{Alignment, Event, Options, Parser, Tag};
+//!
+//! # Spacing across inline tags
+//!
+//! Spaces are insert between CJK and Western text on the two sides of a boundary, as if the markup
+//! in between was not there. The space goes outside of the markup, before an opening tag and after
+//! a closing one. A footnote reference sticks to the text before it, and an image without alt text
+//! is seen through, so the space goes after either.
+//!
+//! ```plain
+//! 中文[Rust](/)中文     => 中文 <a>Rust</a> 中文         links, emphasis and code
+//! 中文<kbd>Rust</kbd>   => 中文 <kbd>Rust</kbd>          inline HTML
+//! 中文[^1]Rust          => 中文<sup>1</sup> Rust         footnote references
+//! 中文![Rust](r.png)中文 => 中文 <img alt="Rust"> 中文    images, as their alt text
+//! 中文![](r.png)Rust    => 中文<img alt=""> Rust         images without alt text
+//! ```
+//!
+//! Blocks, table cells, line breaks, `<br>` and raw HTML blocks end the text, no space is insert
+//! across them.
+use asset::{is_asset, local, target, Asset};
+use config::{ASSETS, CODE, EXTERNAL, HTML, INTERNAL, LANG, LINTER, NOTES, SITE};
+use escape::{Attr, Text, Url};
+use front::fields;
+use image::size;
+use linter::{edges, lint_with, Context, Lang, Linter, Scripts, Tally};
+use math::mathml;
+use pulldown_cmark::{
+    Alignment, CodeBlockKind, CowStr, Event, HeadingLevel, Options, Parser, Tag, TagEnd,
+};
+use sanitize::{unsafe_scheme, Sanitizer};
 use std::borrow::Cow;
 use std::collections::HashMap;
-use std::fmt::{Arguments, Write};
+use std::fmt::Write;
+use std::mem;
+use std::path::{Path, PathBuf};
 
 #[derive(Eq, PartialEq)]
 pub struct Post {
@@ -24,8 +54,57 @@ pub struct Post {
     pub released: String,
     pub modified: String,
     pub category: String,
+    pub tags: Vec<String>,
     pub pagename: String,
+    pub lang: Lang,
     pub data: String,
+    /// The input file, `-` for stdin.
+    pub path: PathBuf,
+    pub assets: Vec<Asset>,
+    /// Pagenames of the posts it refers to by `post:pagename`.
+    pub refs: Vec<String>,
+    /// What the author had better fix but the post builds with, like footnotes never cited.
+    pub warnings: Vec<String>,
+}
+
+/// Where footnotes go, their ids are namespaced by the pagename of the post.
+#[allow(dead_code)] // The one in use is chosen in config.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Notes {
+    /// An ordered list at the end of the post.
+    End,
+    /// An aside after the top-level block citing it first, for CSS to float aside.
+    Side,
+}
+
+/// Attributes of links of a kind, internal or external by the base URL of the site.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct Links {
+    /// Where to open the link, `_blank` for a new tab.
+    pub target: Option<&'static str>,
+    /// Link types, like `noopener noreferrer`.
+    pub rel: Option<&'static str>,
+    /// Adds `nofollow` to the link types, telling search engines not to endorse it.
+    pub nofollow: bool,
+    /// Class for CSS to mark the link, like with an icon.
+    pub class: Option<&'static str>,
+}
+
+impl Links {
+    fn write(&self, w: &mut String) {
+        if let Some(target) = self.target {
+            write!(w, " target=\"{}\"", target).unwrap();
+        }
+        match (self.rel, self.nofollow) {
+            (Some(rel), true) => write!(w, " rel=\"{} nofollow\"", rel).unwrap(),
+            (Some(rel), false) => write!(w, " rel=\"{}\"", rel).unwrap(),
+            (None, true) => w.push_str(" rel=\"nofollow\""),
+            (None, false) => (),
+        }
+        if let Some(class) = self.class {
+            write!(w, " class=\"{}\"", class).unwrap();
+        }
+    }
 }
 
 enum TableState {
@@ -35,63 +114,163 @@ enum TableState {
 
 pub struct Blog<'a> {
     iter: Parser<'a>,
-    size: usize,
+    done: bool,
+    /// The content starts with front matter, and holds one post.
+    front: bool,
 
     title: String,
     released: String,
     modified: String,
     category: String,
+    tags: Vec<String>,
     pagename: String,
+    lang: Option<Lang>,
     data: String,
+    path: PathBuf,
+    assets: Vec<Asset>,
+    refs: Vec<String>,
+    warnings: Vec<String>,
+    xref: Option<(String, String)>,
 
-    reference: HashMap<Cow<'a, str>, usize>,
+    tally: Tally,
+    reference: HashMap<CowStr<'a>, usize>,
+    citations: Vec<(CowStr<'a>, usize)>,
+    notes: HashMap<CowStr<'a>, String>,
+    note: Option<(CowStr<'a>, String)>,
+    options: ::linter::Options,
+    placement: Notes,
+    depth: usize,
+    pending: Vec<usize>,
+    paragraph: Option<usize>,
+    figure: Option<(usize, CowStr<'a>)>,
+    sidenotes: Vec<(usize, usize)>,
     space_state: Scripts,
     space_buffer: String,
+    context: Context,
+    code: bool,
+    sanitizer: Sanitizer,
     table_state: TableState,
     table_alignments: Vec<Alignment>,
     table_cell_index: usize,
 }
 
+/// Returns the markdown events of content, with the extensions Blog understands. Smart punctuation
+/// is left to the linter, which knows about CJK quotes.
+pub fn markdown<'a>(content: &'a str) -> Parser<'a> {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_HEADING_ATTRIBUTES
+        | Options::ENABLE_DEFINITION_LIST
+        | Options::ENABLE_MATH
+        | Options::ENABLE_YAML_STYLE_METADATA_BLOCKS
+        | Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS;
+    Parser::new_ext(content, options)
+}
+
+/// Returns if event starts a post.
+pub fn title(event: &Event) -> bool {
+    matches!(
+        *event,
+        Event::Start(Tag::Heading {
+            level: HeadingLevel::H1,
+            ..
+        })
+    )
+}
+
 impl<'a> Blog<'a> {
     pub fn from(content: &'a str) -> Blog<'a> {
-        let mut iter = Parser::new_ext(content, Options::ENABLE_TABLES);
-
-        while let Some(event) = iter.next() {
-            if event == Event::Start(Tag::Header(1)) {
-                break;
-            }
-        }
+        let mut iter = markdown(content);
+        let (front, done) = match iter.next() {
+            Some(Event::Start(Tag::MetadataBlock(_))) => (true, false),
+            Some(ref event) if title(event) => (false, false),
+            Some(_) => (false, !iter.by_ref().any(|x| title(&x))),
+            None => (false, true),
+        };
 
         Blog {
             iter,
-            size: content.len(),
+            done,
+            front,
 
             title: String::with_capacity(64),
             released: String::with_capacity(25),
             modified: String::with_capacity(25),
             category: String::with_capacity(16),
+            tags: Vec::new(),
             pagename: String::with_capacity(32),
+            lang: None,
             data: String::with_capacity(16384),
+            path: PathBuf::from("-"),
+            assets: Vec::new(),
+            refs: Vec::new(),
+            warnings: Vec::new(),
+            xref: None,
 
+            tally: Tally::default(),
             reference: HashMap::default(),
+            citations: Vec::new(),
+            notes: HashMap::default(),
+            note: None,
+            options: LINTER,
+            placement: NOTES,
+            depth: 0,
+            pending: Vec::new(),
+            paragraph: None,
+            figure: None,
+            sidenotes: Vec::new(),
             space_state: Scripts::Unknown,
             space_buffer: String::with_capacity(64),
+            context: Context::default(),
+            code: false,
+            sanitizer: Sanitizer::new(&HTML),
             table_state: TableState::Head,
             table_alignments: Vec::with_capacity(8),
             table_cell_index: 0,
         }
     }
 
+    /// Name the input file, for diagnostics and to resolve relative targets against its directory.
+    pub fn path(mut self, path: &Path) -> Blog<'a> {
+        self.path = path.to_path_buf();
+        self
+    }
+
+    /// Returns the directory of the input file.
+    fn root(&self) -> &Path {
+        self.path.parent().unwrap_or_else(|| Path::new(""))
+    }
+
     fn clear(&mut self) {
         self.title.clear();
         self.released.clear();
         self.modified.clear();
         self.category.clear();
+        self.tags.clear();
         self.pagename.clear();
+        self.lang = None;
         self.data.clear();
+        self.assets.clear();
+        self.refs.clear();
+        self.warnings.clear();
+        self.xref = None;
+        self.tally = Tally::default();
         self.reference.clear();
+        self.citations.clear();
+        self.notes.clear();
+        self.note = None;
+        self.depth = 0;
+        self.pending.clear();
+        self.paragraph = None;
+        self.figure = None;
+        self.sidenotes.clear();
         self.space_state = Scripts::Unknown;
         self.space_buffer.clear();
+        self.context = Context::default();
+        self.code = false;
+        self.sanitizer.reset();
         self.table_state = TableState::Head;
         self.table_alignments.clear();
         self.table_cell_index = 0;
@@ -99,11 +278,16 @@ impl<'a> Blog<'a> {
 
     fn parse_meta(&mut self) {
         let mut header = true;
-        while let Some(event) = self.iter.next() {
+        for event in self.iter.by_ref() {
             match event {
                 Event::Start(Tag::CodeBlock(_)) => header = false,
+                Event::Code(ref text) if header => {
+                    self.tally.push(text);
+                    self.title.push_txt(text, &self.options.plain());
+                }
                 Event::Text(ref text) if header => {
-                    self.title.push_txt(text);
+                    self.tally.push(text);
+                    self.title.push_txt(text, &self.options.plain());
                 }
                 Event::Text(ref text) if text.starts_with("本文发表于：") => {
                     self.released.push_str(text[18..].trim_end());
@@ -114,54 +298,153 @@ impl<'a> Blog<'a> {
                 Event::Text(ref text) if text.starts_with("分类：") => {
                     self.category.push_str(text[9..].trim_end());
                 }
+                Event::Text(ref text) if text.starts_with("标签：") => {
+                    let tags = text[9..].split(['，', ',', '、']).map(str::trim);
+                    self.tags
+                        .extend(tags.filter(|x| !x.is_empty()).map(String::from));
+                }
                 Event::Text(ref text) if text.starts_with("页名：") => {
                     self.pagename.push_str(text[9..].trim_end());
                 }
-                Event::End(Tag::CodeBlock(_)) => break,
+                Event::Text(ref text) if text.starts_with("语言：") => {
+                    self.lang = Lang::from_tag(&text[9..]);
+                }
+                Event::End(TagEnd::CodeBlock) => break,
+                _ => (),
+            }
+        }
+    }
+
+    /// Read the front matter instead, the keys of other generators map onto the same fields. The
+    /// pagename defaults to the file name.
+    fn parse_front(&mut self) {
+        let mut text = String::new();
+        let mut kind = None;
+        for event in self.iter.by_ref() {
+            match event {
+                Event::Text(ref t) => text.push_str(t),
+                Event::End(TagEnd::MetadataBlock(x)) => {
+                    kind = Some(x);
+                    break;
+                }
                 _ => (),
             }
         }
+        for (key, values) in kind.map_or_else(Vec::new, |x| fields(&text, x)) {
+            let value = match values.first() {
+                Some(value) => value,
+                None => continue,
+            };
+            match key.to_ascii_lowercase().as_str() {
+                "title" => {
+                    self.tally.push(value);
+                    self.title.clear();
+                    self.title.push_txt(value, &self.options.plain());
+                }
+                "date" | "published" => self.released = value.clone(),
+                "lastmod" | "updated" | "modified" => self.modified = value.clone(),
+                "categories" | "category" => self.category = value.clone(),
+                "tags" => self.tags = values,
+                "slug" | "pagename" => self.pagename = value.clone(),
+                "lang" | "language" => self.lang = Lang::from_tag(value),
+                _ => (),
+            }
+        }
+        if self.pagename.is_empty() && self.path != Path::new("-") {
+            let stem = self.path.file_stem().unwrap_or_default();
+            self.pagename = stem.to_string_lossy().into_owned();
+        }
     }
 
     fn parse_body(&mut self) {
+        self.done = true;
+        // If the last event is a `[` text, an undefined footnote is cited if `^name` follows.
+        let mut bracket = false;
         while let Some(event) = self.iter.next() {
+            let last = mem::replace(
+                &mut bracket,
+                matches!(event, Event::Text(ref x) if x.as_ref() == "["),
+            );
             match event {
-                Event::Start(Tag::Header(1)) => break,
+                ref event if !self.front && title(event) => {
+                    self.done = false;
+                    break;
+                }
                 Event::Start(tag) => self.start_tag(tag),
                 Event::End(tag) => self.end_tag(tag),
-                Event::Text(text) => self.push_text(&text),
-                Event::Html(html) => self.push_html(&html),
-                Event::InlineHtml(html) => self.push_html(&html),
-                Event::SoftBreak => self.fresh_line(),
+                Event::Text(text) => {
+                    if last && text.starts_with('^') && !text.contains(char::is_whitespace) {
+                        let message = format!("footnote [{}] is not defined", text);
+                        self.warnings.push(message);
+                    }
+                    self.push_text(&text);
+                }
+                Event::Code(text) => {
+                    self.space_buffer.push_str("<code>");
+                    self.code = true;
+                    self.push_text(&text);
+                    self.code = false;
+                    self.data.push_str("</code>");
+                }
+                Event::Html(html) => {
+                    let html = self.clean(&html);
+                    self.push_html(&html);
+                }
+                Event::InlineHtml(html) => {
+                    let html = self.clean(&html);
+                    if !html.is_empty() {
+                        self.push_inline(&html);
+                    }
+                }
+                Event::SoftBreak => {
+                    self.fresh_line();
+                    self.context.push('\n');
+                }
                 Event::HardBreak => self.push_html("<br />\n"),
+                Event::Rule => {
+                    self.fresh_line();
+                    self.push_html("<hr />\n");
+                }
+                Event::TaskListMarker(checked) => {
+                    self.space_buffer.push_str(if checked {
+                        "<input type=\"checkbox\" checked disabled /> "
+                    } else {
+                        "<input type=\"checkbox\" disabled /> "
+                    });
+                }
                 Event::FootnoteReference(name) => {
                     let id = self.poll_note(name);
-                    self.push_note(format_args!(
-                        "\u{2060}<sup><a href=\"#{0}\">{0}</a></sup>",
-                        id
-                    ));
+                    self.push_note(id);
                 }
+                Event::InlineMath(tex) => self.push_math(&tex, false),
+                Event::DisplayMath(tex) => self.push_math(&tex, true),
             }
         }
         self.fresh_buffer();
+        self.push_notes();
     }
 
-    fn parse_text(&mut self) {
+    /// Collect the text till the end of current tag, markup is dropped.
+    fn parse_text(&mut self) -> String {
         let mut nest = 0;
+        let mut result = String::new();
         while let Some(event) = self.iter.next() {
             match event {
                 Event::Start(_) => nest += 1,
                 Event::End(_) if nest == 0 => break,
                 Event::End(_) => nest -= 1,
-                Event::Text(text) => self.push_html(&text),
+                Event::Text(text) | Event::Code(text) => result.push_str(&text),
+                Event::InlineMath(text) | Event::DisplayMath(text) => result.push_str(&text),
                 Event::Html(_) | Event::InlineHtml(_) => (),
-                Event::SoftBreak | Event::HardBreak => self.push_html(" "),
+                Event::Rule | Event::TaskListMarker(_) => (),
+                Event::SoftBreak | Event::HardBreak => result.push(' '),
                 Event::FootnoteReference(name) => {
                     let id = self.poll_note(name);
-                    self.push_note(format_args!("[{0}]", id));
+                    write!(&mut result, "[{0}]", id).unwrap();
                 }
             }
         }
+        result
     }
 
     fn fresh_buffer(&mut self) {
@@ -183,48 +466,250 @@ impl<'a> Blog<'a> {
         self.fresh_buffer();
         self.data.push_str(text);
         self.space_state = Scripts::Unknown;
+        self.context.push('\n');
     }
 
-    fn push_text(&mut self, text: &str) {
+    /// Returns what the policy allows of raw html.
+    fn clean(&mut self, html: &str) -> String {
+        let mut result = String::with_capacity(html.len());
+        self.sanitizer.clean(&mut result, html);
+        result
+    }
+
+    /// Inline HTML is transparent like inline tags, except `<br>` which ends the text.
+    fn push_inline(&mut self, html: &str) {
+        let name = html
+            .trim_start_matches('<')
+            .split(|x: char| !x.is_ascii_alphanumeric())
+            .next()
+            .unwrap();
+        if name.eq_ignore_ascii_case("br") {
+            self.push_html(html);
+        } else if html.starts_with("</") {
+            self.fresh_buffer();
+            self.data.push_str(html);
+        } else {
+            self.space_buffer.push_str(html);
+        }
+    }
+
+    /// Insert a space if text continues the text before it in another script.
+    fn push_space(&mut self, text: &str) {
         let ws = self.space_state;
-        let ns = text.chars().next().map_or(Scripts::Unknown, |x| x.into());
+        let ns = text.chars().next().map_or(Scripts::Unknown, |x| edges(x).0);
 
-        if (ws == Scripts::Chinese && ns != Scripts::Unknown)
-            || (ns == Scripts::Chinese && ws != Scripts::Unknown) && ws != ns
-        {
-            self.data.push('\u{2009}');
+        if self.options.gap(ws, ns) {
+            self.data.push_str(self.options.spacing.as_str());
         };
 
+        if let Some(last) = text.chars().last() {
+            self.space_state = edges(last).1;
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        self.push_space(text);
         self.fresh_buffer();
-        self.data.push_txt(text);
-        self.space_state = text.chars().last().map_or(Scripts::Unknown, |x| x.into());
+        let options = if self.code { &CODE } else { &self.options };
+        if !self.code {
+            self.tally.push(text);
+        }
+        lint_with(&mut self.data, text, options, &mut self.context).unwrap();
     }
 
-    fn push_note(&mut self, args: Arguments) {
+    /// Math is a Western part as a whole, rendered to MathML and left alone by the linter. What is
+    /// not supported stays TeX.
+    fn push_math(&mut self, tex: &str, display: bool) {
+        if self.options.gap(self.space_state, Scripts::English) {
+            self.data.push_str(self.options.spacing.as_str());
+        }
+        self.space_state = Scripts::English;
         self.fresh_buffer();
-        self.data.write_fmt(args).unwrap();
-        self.space_state = Scripts::Unknown;
+        match mathml(tex, display) {
+            Ok(math) => self.data.push_str(&math),
+            Err(_) => {
+                let class = if display { "tex display" } else { "tex" };
+                write!(
+                    &mut self.data,
+                    "<code class=\"{}\">{}</code>",
+                    class,
+                    Text(tex)
+                )
+                .unwrap();
+            }
+        }
+        if let Some(last) = tex.chars().last() {
+            self.context.push(last);
+        }
+    }
+
+    /// Returns dest, or the URL of the asset it refers to, which the post then carries.
+    fn asset<'b>(&mut self, dest: &'b str) -> Cow<'b, str> {
+        if !is_asset(dest) {
+            return Cow::Borrowed(dest);
+        }
+        let source = local(self.root(), dest).unwrap();
+        let target = target(ASSETS, &source, &[&self.category, &self.pagename]);
+        let asset = Asset { source, target };
+        let suffix = dest.find(['?', '#']).map_or("", |idx| &dest[idx..]);
+        let url = asset.url() + suffix;
+        if !self.assets.contains(&asset) {
+            self.assets.push(asset);
+        }
+        Cow::Owned(url)
+    }
+
+    /// Footnote references are transparent like inline tags.
+    fn push_note(&mut self, id: usize) {
+        let count = &mut self.citations[id - 1].1;
+        *count += 1;
+        if *count == 1 {
+            self.pending.push(id);
+        }
+        let count = *count;
+        self.fresh_buffer();
+        write!(
+            &mut self.data,
+            "\u{2060}<sup id=\"fnref-{}-{}-{}\"><a href=\"#fn-{}-{1}\">{1}</a></sup>",
+            Attr(&self.pagename),
+            id,
+            count,
+            Url(&self.pagename)
+        )
+        .unwrap();
     }
 
-    fn poll_note(&mut self, name: Cow<'a, str>) -> usize {
-        let id = self.reference.len() + 1;
-        *self.reference.entry(name).or_insert(id)
+    /// Returns the id of the footnote, numbered in the order of first citation.
+    fn poll_note(&mut self, name: CowStr<'a>) -> usize {
+        if let Some(&id) = self.reference.get(&name) {
+            return id;
+        }
+        self.citations.push((name.clone(), 0));
+        self.reference.insert(name, self.citations.len());
+        self.citations.len()
+    }
+
+    /// Returns the footnote id with links back to its citations.
+    fn note_html(&self, id: usize) -> Option<String> {
+        let (ref name, count) = self.citations[id - 1];
+        let note = self.notes.get(name)?;
+        let mut links = String::new();
+        for idx in 1..count + 1 {
+            write!(
+                &mut links,
+                " <a href=\"#fnref-{}-{}-{}\">\u{21A9}\u{FE0E}</a>",
+                Url(&self.pagename),
+                id,
+                idx
+            )
+            .unwrap();
+        }
+        Some(if note.ends_with("</p>\n") {
+            format!("{}{}</p>\n", &note[..note.len() - 5], links)
+        } else {
+            format!("{}<p>{}</p>\n", note, links.trim_start())
+        })
+    }
+
+    /// Write the footnotes cited, unused definitions are dropped.
+    fn push_notes(&mut self) {
+        let mut unused: Vec<&str> = self
+            .notes
+            .keys()
+            .filter(|x| !self.reference.contains_key(*x))
+            .map(|x| &**x)
+            .collect();
+        unused.sort();
+        for name in unused {
+            let message = format!("footnote [^{}] is never cited", name);
+            self.warnings.push(message);
+        }
+        match self.placement {
+            Notes::End => {
+                if self.citations.is_empty() {
+                    return;
+                }
+                let mut notes = String::from("<section class=\"footnotes\">\n<ol>\n");
+                for id in 1..self.citations.len() + 1 {
+                    if let Some(note) = self.note_html(id) {
+                        write!(
+                            &mut notes,
+                            "<li id=\"fn-{}-{}\">\n{}</li>\n",
+                            Attr(&self.pagename),
+                            id,
+                            note
+                        )
+                        .unwrap();
+                    }
+                }
+                notes.push_str("</ol>\n</section>\n");
+                self.data.push_str(&notes);
+            }
+            Notes::Side => {
+                let end = self.data.len();
+                self.sidenotes
+                    .extend(self.pending.drain(..).map(|id| (end, id)));
+                let mut data = String::with_capacity(self.data.len() * 2);
+                let mut copied = 0;
+                for &(offset, id) in &self.sidenotes {
+                    data.push_str(&self.data[copied..offset]);
+                    copied = offset;
+                    if let Some(note) = self.note_html(id) {
+                        write!(
+                            &mut data,
+                            "<aside class=\"sidenote\" id=\"fn-{}-{}\"><sup>{1}</sup>\n{}</aside>\n",
+                            Attr(&self.pagename),
+                            id,
+                            note
+                        )
+                        .unwrap();
+                    }
+                }
+                data.push_str(&self.data[copied..]);
+                self.data = data;
+            }
+        }
     }
 
     fn start_tag(&mut self, tag: Tag<'a>) {
+        if !inline(&tag.to_end()) {
+            self.depth += 1;
+            self.paragraph = None;
+        }
         match tag {
             Tag::Paragraph => {
                 self.fresh_line();
                 self.data.push_str("<p>");
+                self.paragraph = Some(self.data.len());
             }
-            Tag::Rule => {
+            Tag::Heading {
+                level,
+                id,
+                classes,
+                attrs,
+            } => {
                 self.fresh_line();
-                self.data.push_str("<hr />\n")
-            }
-            Tag::Header(level) => {
-                self.fresh_line();
-                self.data.push_str("<h");
-                self.data.push((b'0' + level as u8) as char);
+                write!(&mut self.data, "<h{}", level as usize).unwrap();
+                if let Some(id) = id {
+                    write!(&mut self.data, " id=\"{}\"", Attr(&id)).unwrap();
+                }
+                if !classes.is_empty() {
+                    write!(&mut self.data, " class=\"{}\"", Attr(&classes.join(" "))).unwrap();
+                }
+                // Attributes the raw HTML policy allows, but URLs, which are not checked here.
+                for (name, value) in attrs {
+                    if !HTML.attributes.contains(&&*name)
+                        || name.as_ref() == "href"
+                        || name.as_ref() == "src"
+                    {
+                        continue;
+                    }
+                    match value {
+                        Some(value) => write!(&mut self.data, " {}=\"{}\"", name, Attr(&value)),
+                        None => write!(&mut self.data, " {}", name),
+                    }
+                    .unwrap();
+                }
                 self.data.push('>');
             }
             Tag::Table(alignments) => {
@@ -251,21 +736,24 @@ impl<'a> Blog<'a> {
                     Some(&Alignment::Right) => self.space_buffer.push_str(" align=\"right\""),
                     _ => (),
                 }
-                self.space_buffer.push_str(">");
+                self.space_buffer.push('>');
             }
-            Tag::BlockQuote => {
+            Tag::BlockQuote(_) => {
                 self.fresh_line();
                 self.data.push_str("<blockquote>\n");
             }
-            Tag::CodeBlock(info) => {
+            Tag::CodeBlock(kind) => {
                 self.fresh_line();
+                self.code = true;
+                let info = match kind {
+                    CodeBlockKind::Fenced(info) => info,
+                    CodeBlockKind::Indented => CowStr::Borrowed(""),
+                };
                 let lang = info.split(' ').next().unwrap();
                 if lang.is_empty() {
                     self.data.push_str("<pre><code>");
                 } else {
-                    self.data.push_str("<pre><code lang=\"");
-                    self.data.push_str(lang);
-                    self.data.push_str("\">");
+                    write!(&mut self.data, "<pre><code lang=\"{}\">", Attr(lang)).unwrap();
                 }
             }
             Tag::List(Some(1)) => {
@@ -274,7 +762,7 @@ impl<'a> Blog<'a> {
             }
             Tag::List(Some(start)) => {
                 self.fresh_line();
-                write!(&mut self.data, "<ol start=\"{}\">\n", start).unwrap();
+                writeln!(&mut self.data, "<ol start=\"{}\">", start).unwrap();
             }
             Tag::List(None) => {
                 self.fresh_line();
@@ -284,96 +772,670 @@ impl<'a> Blog<'a> {
                 self.fresh_line();
                 self.data.push_str("<li>");
             }
+            Tag::DefinitionList => {
+                self.fresh_line();
+                self.data.push_str("<dl>\n");
+            }
+            Tag::DefinitionListTitle => {
+                self.fresh_line();
+                self.data.push_str("<dt>");
+            }
+            Tag::DefinitionListDefinition => {
+                self.fresh_line();
+                self.data.push_str("<dd>");
+            }
             Tag::Emphasis => self.space_buffer.push_str("<em>"),
             Tag::Strong => self.space_buffer.push_str("<strong>"),
-            Tag::Code => self.space_buffer.push_str("<code>"),
-            Tag::Link(dest, title) => {
-                self.space_buffer.push_str("<a href=\"");
-                self.space_buffer.push_str(&dest);
+            Tag::Strikethrough => self.space_buffer.push_str("<del>"),
+            Tag::Superscript => self.space_buffer.push_str("<sup>"),
+            Tag::Subscript => self.space_buffer.push_str("<sub>"),
+            Tag::Link {
+                dest_url: dest,
+                title,
+                ..
+            } => {
+                let opening = self.space_buffer.len();
+                // A cross-reference to another post, resolved by Site once all are parsed.
+                let xref = dest
+                    .strip_prefix("post:")
+                    .map(|x| x.split('#').next().unwrap().to_string());
+                let dest = match xref {
+                    Some(_) => Cow::Borrowed(&*dest),
+                    None if unsafe_scheme(&dest, false).is_some() => Cow::Borrowed("#"),
+                    None => self.asset(&dest),
+                };
+                write!(&mut self.space_buffer, "<a href=\"{}", Url(&dest)).unwrap();
                 if !title.is_empty() {
                     self.space_buffer.push_str("\" title=\"");
-                    self.space_buffer.push_txt(title);
+                    self.space_buffer.push_txt(title, &self.options.attribute());
+                }
+                self.space_buffer.push('"');
+                let links = match xref {
+                    Some(_) => Some(&INTERNAL),
+                    None => links(&dest),
+                };
+                if let Some(links) = links {
+                    links.write(&mut self.space_buffer);
+                }
+                self.space_buffer.push('>');
+                if let Some(name) = xref {
+                    let opening = self.space_buffer[opening..].to_string();
+                    if !self.refs.contains(&name) {
+                        self.refs.push(name.clone());
+                    }
+                    self.xref = Some((name, opening));
                 }
-                self.space_buffer.push_str("\" target=\"_blank\">");
             }
-            Tag::Image(dest, title) => {
-                self.space_buffer.push_str("<img src=\"");
-                self.space_buffer.push_str(&dest);
-                self.space_buffer.push_str("\" alt=\"");
-                self.parse_text();
-                if !title.is_empty() {
-                    self.space_buffer.push_str("\" title=\"");
-                    self.space_buffer.push_txt(title);
+            Tag::Image {
+                dest_url: dest,
+                title,
+                ..
+            } => {
+                // Standalone if it is all the paragraph, which becomes a figure.
+                let figure =
+                    self.paragraph == Some(self.data.len()) && self.space_buffer.is_empty();
+                let alt = self.parse_text();
+                self.push_space(&alt);
+                self.fresh_buffer();
+                let size = local(self.root(), &dest).and_then(|x| size(&x));
+                let dest = match unsafe_scheme(&dest, true) {
+                    Some(_) => Cow::Borrowed("#"),
+                    None => self.asset(&dest),
+                };
+                write!(&mut self.data, "<img src=\"{}\" alt=\"", Url(&dest)).unwrap();
+                self.data.push_txt(alt, &self.options.attribute());
+                if !(title.is_empty() || figure) {
+                    self.data.push_str("\" title=\"");
+                    self.data.push_txt(&title, &self.options.attribute());
+                }
+                self.data.push('"');
+                if let Some((width, height)) = size {
+                    write!(&mut self.data, " width=\"{}\" height=\"{}\"", width, height).unwrap();
+                }
+                self.data
+                    .push_str(" loading=\"lazy\" decoding=\"async\" />");
+                if figure {
+                    self.figure = Some((self.data.len(), title));
                 }
-                self.space_buffer.push_str("\" />")
             }
             Tag::FootnoteDefinition(name) => {
-                let id = self.poll_note(name);
-                self.push_note(format_args!("<aside id=\"{0}\"><sup>{0}</sup>", id));
+                self.fresh_line();
+                let data = mem::take(&mut self.data);
+                self.note = Some((name, data));
             }
+            Tag::HtmlBlock | Tag::MetadataBlock(_) => (),
         }
     }
 
-    fn end_tag(&mut self, tag: Tag) {
+    fn end_tag(&mut self, tag: TagEnd) {
         self.fresh_buffer();
         match tag {
-            Tag::Paragraph => self.data.push_str("</p>\n"),
-            Tag::Rule => (),
-            Tag::Header(level) => {
-                self.data.push_str("</h");
-                self.data.push((b'0' + level as u8) as char);
-                self.data.push_str(">\n");
-            }
-            Tag::Table(_) => {
+            TagEnd::Paragraph => match (self.paragraph, self.figure.take()) {
+                (Some(start), Some((end, title))) if end == self.data.len() => {
+                    self.data.replace_range(start - 3..start, "<figure>");
+                    if !title.is_empty() {
+                        self.data.push_str("\n<figcaption>");
+                        self.data.push_txt(title, &self.options.plain());
+                        self.data.push_str("</figcaption>");
+                    }
+                    self.data.push_str("</figure>\n");
+                }
+                _ => self.data.push_str("</p>\n"),
+            },
+            TagEnd::Heading(level) => writeln!(&mut self.data, "</h{}>", level as usize).unwrap(),
+            TagEnd::Table => {
                 self.data.push_str("</tbody></table>\n");
             }
-            Tag::TableHead => {
+            TagEnd::TableHead => {
                 self.data.push_str("</tr></thead><tbody>\n");
                 self.table_state = TableState::Body;
             }
-            Tag::TableRow => {
+            TagEnd::TableRow => {
                 self.data.push_str("</tr>\n");
             }
-            Tag::TableCell => {
+            TagEnd::TableCell => {
                 match self.table_state {
                     TableState::Head => self.data.push_str("</th>"),
                     TableState::Body => self.data.push_str("</td>"),
                 }
                 self.table_cell_index += 1;
             }
-            Tag::BlockQuote => self.data.push_str("</blockquote>\n"),
-            Tag::CodeBlock(_) => self.data.push_str("</code></pre>\n"),
-            Tag::List(Some(_)) => self.data.push_str("</ol>\n"),
-            Tag::List(None) => self.data.push_str("</ul>\n"),
-            Tag::Item => self.data.push_str("</li>\n"),
-            Tag::Emphasis => self.data.push_str("</em>"),
-            Tag::Strong => self.data.push_str("</strong>"),
-            Tag::Code => self.data.push_str("</code>"),
-            Tag::Link(_, _) => self.data.push_str("</a>"),
-            Tag::Image(_, _) => (),
-            Tag::FootnoteDefinition(_) => self.data.push_str("</aside>\n"),
+            TagEnd::BlockQuote(_) => self.data.push_str("</blockquote>\n"),
+            TagEnd::CodeBlock => {
+                self.code = false;
+                self.data.push_str("</code></pre>\n");
+            }
+            TagEnd::List(true) => self.data.push_str("</ol>\n"),
+            TagEnd::List(false) => self.data.push_str("</ul>\n"),
+            TagEnd::Item => self.data.push_str("</li>\n"),
+            TagEnd::DefinitionList => self.data.push_str("</dl>\n"),
+            TagEnd::DefinitionListTitle => self.data.push_str("</dt>\n"),
+            TagEnd::DefinitionListDefinition => self.data.push_str("</dd>\n"),
+            TagEnd::Emphasis => self.data.push_str("</em>"),
+            TagEnd::Strong => self.data.push_str("</strong>"),
+            TagEnd::Strikethrough => self.data.push_str("</del>"),
+            TagEnd::Superscript => self.data.push_str("</sup>"),
+            TagEnd::Subscript => self.data.push_str("</sub>"),
+            TagEnd::Link => {
+                // A cross-reference without text shows the title of the post.
+                if let Some((name, opening)) = self.xref.take() {
+                    if self.data.ends_with(&opening) {
+                        write!(&mut self.data, "<!--post:{}-->", Url(&name)).unwrap();
+                    }
+                }
+                self.data.push_str("</a>");
+            }
+            TagEnd::Image => (),
+            TagEnd::FootnoteDefinition => {
+                if let Some((name, data)) = self.note.take() {
+                    let note = mem::replace(&mut self.data, data);
+                    self.notes.insert(name, note);
+                }
+            }
+            TagEnd::HtmlBlock | TagEnd::MetadataBlock(_) => (),
+        }
+        if !inline(&tag) {
+            self.space_state = Scripts::Unknown;
+            self.context = Context::default();
+            self.depth -= 1;
+            if self.depth == 0 && self.note.is_none() {
+                let end = self.data.len();
+                self.sidenotes
+                    .extend(self.pending.drain(..).map(|id| (end, id)));
+            }
         }
     }
 }
 
+/// Returns the attributes of a link to dest, None for anchors and schemes other than the web.
+fn links(dest: &str) -> Option<&'static Links> {
+    // Host of a URL starting with `//`, lowercased as hosts are case-insensitive.
+    let host = |url: &str| {
+        url[2..]
+            .split(['/', '?', '#'])
+            .next()
+            .unwrap()
+            .to_ascii_lowercase()
+    };
+    let site = host(&SITE[SITE.find("//")?..]);
+    if dest.is_empty() || dest.starts_with('#') {
+        return None;
+    }
+    let path = dest.split(['/', '?', '#']).next().unwrap();
+    let url = match path.find(':') {
+        Some(idx)
+            if ["http", "https"]
+                .iter()
+                .any(|x| x.eq_ignore_ascii_case(&path[..idx])) =>
+        {
+            &dest[idx + 1..]
+        }
+        Some(_) => return None,
+        None => dest,
+    };
+    if url.starts_with("//") && host(url) != site {
+        Some(&EXTERNAL)
+    } else {
+        Some(&INTERNAL)
+    }
+}
+
+/// Returns if the tag is inline, transparent to spacing.
+fn inline(tag: &TagEnd) -> bool {
+    matches!(
+        *tag,
+        TagEnd::Emphasis
+            | TagEnd::Strong
+            | TagEnd::Strikethrough
+            | TagEnd::Superscript
+            | TagEnd::Subscript
+            | TagEnd::Link
+            | TagEnd::Image
+    )
+}
+
 impl<'a> Iterator for Blog<'a> {
     type Item = Post;
 
     fn next(&mut self) -> Option<Post> {
-        if self.iter.get_offset() < self.size {
+        if !self.done {
             self.clear();
-            self.parse_meta();
+            if self.front {
+                self.parse_front();
+            } else {
+                self.parse_meta();
+            }
+            if self.modified.is_empty() {
+                self.modified = self.released.clone();
+            }
             self.parse_body();
+            // The buffers move into the post, the next one starts a fresh one of the same size.
+            let data = String::with_capacity(self.data.capacity());
             Some(Post {
-                title: self.title.clone(),
-                released: self.released.clone(),
-                modified: self.modified.clone(),
-                category: self.category.clone(),
-                pagename: self.pagename.clone(),
-                data: self.data.clone(),
+                title: mem::take(&mut self.title),
+                released: mem::take(&mut self.released),
+                modified: mem::take(&mut self.modified),
+                category: mem::take(&mut self.category),
+                tags: mem::take(&mut self.tags),
+                pagename: mem::take(&mut self.pagename),
+                lang: self.lang.or_else(|| self.tally.lang()).unwrap_or(LANG),
+                data: mem::replace(&mut self.data, data),
+                path: self.path.clone(),
+                assets: mem::take(&mut self.assets),
+                refs: mem::take(&mut self.refs),
+                warnings: mem::take(&mut self.warnings),
             })
         } else {
             None
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Blog, Notes};
+    use config::LINTER;
+    use linter::{Lang, Options, Typography};
+    use std::path::Path;
+
+    fn render(body: &str) -> String {
+        render_with(body, LINTER)
+    }
+
+    fn render_with(body: &str, options: Options) -> String {
+        let content = format!(
+            "# Title\n\n    本文发表于：2018-01-01\n    页名：post\n\n{}\n",
+            body
+        );
+        let mut blog = Blog::from(&content);
+        blog.options = options;
+        blog.next().unwrap().data
+    }
+
+    #[test]
+    fn test_spacing() {
+        let cases = [
+            ("中文Rust中文", "<p>中文\u{2009}Rust\u{2009}中文</p>\n"),
+            ("中文*Rust*中文", "<p>中文\u{2009}<em>Rust</em>\u{2009}中文</p>\n"),
+            ("*中文*Rust", "<p><em>中文</em>\u{2009}Rust</p>\n"),
+            ("中文`Rust`**中文**", "<p>中文\u{2009}<code>Rust</code>\u{2009}<strong>中文</strong></p>\n"),
+            (
+                "中文[Rust](/)中文",
+                "<p>中文\u{2009}<a href=\"/\">Rust</a>\u{2009}中文</p>\n",
+            ),
+            (
+                "[中文](/)[Rust](/)",
+                "<p><a href=\"/\">中文</a>\u{2009}<a href=\"/\">Rust</a></p>\n",
+            ),
+            ("中文<kbd>Rust</kbd>中文", "<p>中文\u{2009}<kbd>Rust</kbd>\u{2009}中文</p>\n"),
+            ("中文<br>Rust", "<p>中文<br>Rust</p>\n"),
+            ("中文  \nRust", "<p>中文<br />\nRust</p>\n"),
+            ("中文\nRust", "<p>中文\nRust</p>\n"),
+            (
+                "中文![Rust](r.png)中文",
+                "<p>中文\u{2009}<img src=\"/post/r.png\" alt=\"Rust\" loading=\"lazy\" \
+                 decoding=\"async\" />\u{2009}中文</p>\n",
+            ),
+            (
+                "Rust![图片](r.png \"标题\")",
+                "<p>Rust\u{2009}<img src=\"/post/r.png\" alt=\"图片\" title=\"标题\" loading=\"lazy\" \
+                 decoding=\"async\" /></p>\n",
+            ),
+            (
+                "中文![](r.png)Rust",
+                "<p>中文<img src=\"/post/r.png\" alt=\"\" loading=\"lazy\" decoding=\"async\" />\
+                 \u{2009}Rust</p>\n",
+            ),
+            (
+                "![1 < 2](r.png)",
+                "<figure><img src=\"/post/r.png\" alt=\"1 &lt; 2\" loading=\"lazy\" decoding=\"async\" />\
+                 </figure>\n",
+            ),
+            ("中文\n\nRust", "<p>中文</p>\n<p>Rust</p>\n"),
+            (
+                "|中文|Rust|\n|-|-|\n|Rust|中文|",
+                "<table><thead><tr><th>中文</th><th>Rust</th></tr></thead><tbody>\n\
+                 <tr><td>Rust</td><td>中文</td></tr>\n</tbody></table>\n",
+            ),
+        ];
+        for &(input, output) in cases.iter() {
+            assert_eq!(output, render(input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_typography() {
+        let options = Options {
+            typography: Typography::Curly,
+            ..LINTER
+        };
+        let cases = [
+            ("\"*Rust*\"", "<p>“<em>Rust</em>”</p>\n"),
+            ("'[Rust](/)'", "<p>‘<a href=\"/\">Rust</a>’</p>\n"),
+            ("\"Rust\nRust\"", "<p>“Rust\nRust”</p>\n"),
+            ("\"Rust\n\n\"Rust\"", "<p>“Rust</p>\n<p>“Rust”</p>\n"),
+            ("`\"--\"` -- ...", "<p><code>&#34;--&#34;</code> – …</p>\n"),
+            (
+                "```\n\"...\"\n```",
+                "<pre><code>&#34;...&#34;\n</code></pre>\n",
+            ),
+        ];
+        for &(input, output) in cases.iter() {
+            assert_eq!(output, render_with(input, options), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_lang() {
+        let cases = [
+            ("    本文发表于：2018-01-01", "这是简体中文。", Lang::Hans),
+            ("    本文发表于：2018-01-01", "這是繁體中文。", Lang::Hant),
+            ("    本文发表于：2018-01-01", "Rust and CSS.", Lang::English),
+            (
+                "    本文发表于：2018-01-01\n    语言：zh-TW",
+                "中文",
+                Lang::Hant,
+            ),
+            (
+                "    本文发表于：2018-01-01\n    语言：en",
+                "中文",
+                Lang::English,
+            ),
+        ];
+        for &(meta, body, lang) in cases.iter() {
+            let content = format!("# Title\n\n{}\n\n{}\n", meta, body);
+            assert_eq!(lang, Blog::from(&content).next().unwrap().lang, "{}", body);
+        }
+    }
+
+    #[test]
+    fn test_escape() {
+        let cases = [
+            (
+                "[Rust](/a\"onclick=\"x)",
+                "<p><a href=\"/a%22onclick=%22x\">Rust</a></p>\n",
+            ),
+            (
+                "[Rust](/a 'b\" c')",
+                "<p><a href=\"/a\" title=\"b&#34; c\">Rust</a></p>\n",
+            ),
+            (
+                "![a\"b](/a'><script>.png)",
+                "<figure><img src=\"/a&#39;%3E%3Cscript%3E.png\" alt=\"a&#34;b\" \
+                 loading=\"lazy\" decoding=\"async\" /></figure>\n",
+            ),
+            (
+                "```rust\"><script>\nx\n```",
+                "<pre><code lang=\"rust&#34;&gt;&lt;script&gt;\">x\n</code></pre>\n",
+            ),
+        ];
+        for &(input, output) in cases.iter() {
+            assert_eq!(output, render(input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_sanitize() {
+        let cases = [
+            (
+                "<div onclick=\"x\">\n<script>\nalert(1)\n</script>\n</div>",
+                "<div>\n\n</div>\n",
+            ),
+            (
+                "中文<font>Rust</font>中文",
+                "<p>中文\u{2009}Rust\u{2009}中文</p>\n",
+            ),
+            (
+                "中文<a href=\"javascript:x\">Rust</a>",
+                "<p>中文\u{2009}<a>Rust</a></p>\n",
+            ),
+        ];
+        for &(input, output) in cases.iter() {
+            assert_eq!(output, render(input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_extensions() {
+        let cases = [
+            (
+                "中文~~Rust~~中文",
+                "<p>中文\u{2009}<del>Rust</del>\u{2009}中文</p>\n",
+            ),
+            (
+                "- [ ] Rust任务\n- [x] 中文",
+                "<ul>\n<li><input type=\"checkbox\" disabled /> Rust\u{2009}任务</li>\n\
+                 <li><input type=\"checkbox\" checked disabled /> 中文</li>\n</ul>\n",
+            ),
+            (
+                "## Rust中文 {#rust .a .b lang=en onclick=x}",
+                "<h2 id=\"rust\" class=\"a b\" lang=\"en\">Rust\u{2009}中文</h2>\n",
+            ),
+            (
+                "Rust\n: 中文定义",
+                "<dl>\n<dt>Rust</dt>\n<dd>中文定义</dd>\n</dl>\n",
+            ),
+            ("---", "<hr />\n"),
+        ];
+        for &(input, output) in cases.iter() {
+            assert_eq!(output, render(input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_images() {
+        let cases = [
+            (
+                "![中文Rust](a.png \"标题\")",
+                "<figure><img src=\"/post/a.png\" alt=\"中文\u{2009}Rust\" loading=\"lazy\" \
+                 decoding=\"async\" />\n<figcaption>标题</figcaption></figure>\n",
+            ),
+            (
+                "![Hyphenation](a.png \"Hyphenation\")",
+                "<figure><img src=\"/post/a.png\" alt=\"Hyphenation\" loading=\"lazy\" \
+                 decoding=\"async\" />\n<figcaption>Hy\u{ad}phen\u{ad}ation</figcaption></figure>\n",
+            ),
+            (
+                "看![Rust](https://a.com/a.png \"标题\")图",
+                "<p>看\u{2009}<img src=\"https://a.com/a.png\" alt=\"Rust\" title=\"标题\" \
+                 loading=\"lazy\" decoding=\"async\" />\u{2009}图</p>\n",
+            ),
+            (
+                "![a](a.png)\n![b](b.png)",
+                "<p><img src=\"/post/a.png\" alt=\"a\" loading=\"lazy\" decoding=\"async\" />\n\
+                 <img src=\"/post/b.png\" alt=\"b\" loading=\"lazy\" decoding=\"async\" /></p>\n",
+            ),
+            (
+                "看![图](javascript:alert(2))",
+                "<p>看<img src=\"#\" alt=\"图\" loading=\"lazy\" decoding=\"async\" /></p>\n",
+            ),
+            (
+                "看![图](data:image/png;base64,x)",
+                "<p>看<img src=\"data:image/png;base64,x\" alt=\"图\" loading=\"lazy\" \
+                 decoding=\"async\" /></p>\n",
+            ),
+            (
+                "看![图](data:image/svg+xml,x)",
+                "<p>看<img src=\"data:image/svg+xml,x\" alt=\"图\" loading=\"lazy\" \
+                 decoding=\"async\" /></p>\n",
+            ),
+            (
+                "看![图](data:text/html,x)",
+                "<p>看<img src=\"#\" alt=\"图\" loading=\"lazy\" decoding=\"async\" /></p>\n",
+            ),
+        ];
+        for &(input, output) in cases.iter() {
+            assert_eq!(output, render(input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_links() {
+        let cases = [
+            ("[a](/about/)", "<a href=\"/about/\">"),
+            ("[a](../rust/)", "<a href=\"../rust/\">"),
+            (
+                "[a](https://darknode.in/about/)",
+                "<a href=\"https://darknode.in/about/\">",
+            ),
+            ("[a](HTTP://DarkNode.in)", "<a href=\"HTTP://DarkNode.in\">"),
+            (
+                "[a](https://darknode.in.example.com/)",
+                "<a href=\"https://darknode.in.example.com/\" target=\"_blank\" \
+                 rel=\"noopener noreferrer\" class=\"external\">",
+            ),
+            (
+                "[a](//example.com/)",
+                "<a href=\"//example.com/\" target=\"_blank\" rel=\"noopener noreferrer\" \
+                 class=\"external\">",
+            ),
+            ("[a](#top)", "<a href=\"#top\">"),
+            ("[a](mailto:a@b.com)", "<a href=\"mailto:a@b.com\">"),
+            ("[a](javascript:alert(1))", "<a href=\"#\">"),
+            ("[a]( JaVaScRiPt:alert(1))", "<a href=\"#\">"),
+            ("[a](vbscript:msgbox)", "<a href=\"#\">"),
+            ("[a](data:text/html,x)", "<a href=\"#\">"),
+            ("[a](data:image/png;base64,x)", "<a href=\"#\">"),
+            ("[a](<javascript:alert(1)>)", "<a href=\"#\">"),
+        ];
+        for &(input, output) in cases.iter() {
+            let output = format!("<p>{}a</a></p>\n", output);
+            assert_eq!(output, render(input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_assets() {
+        let content = "# Title\n\n    页名：post\n\n\
+                       [PDF](files/a.pdf#page=2)[页面](../rust/)![图](/img/a.png)\n\n\
+                       ![图](img/b.png)[PDF](files/a.pdf)\n";
+        let post = Blog::from(content)
+            .path(Path::new("posts/a.md"))
+            .next()
+            .unwrap();
+        assert_eq!(
+            "<p><a href=\"/post/a.pdf#page=2\">PDF</a>\u{2009}\
+             <a href=\"../rust/\">页面</a>\
+             <img src=\"/img/a.png\" alt=\"图\" loading=\"lazy\" decoding=\"async\" /></p>\n\
+             <p><img src=\"/post/b.png\" alt=\"图\" loading=\"lazy\" decoding=\"async\" />\u{2009}\
+             <a href=\"/post/a.pdf\">PDF</a></p>\n",
+            post.data
+        );
+        let assets: Vec<_> = post
+            .assets
+            .iter()
+            .map(|x| (x.source.to_str().unwrap(), x.url()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("posts/files/a.pdf", "/post/a.pdf".to_string()),
+                ("posts/img/b.png", "/post/b.png".to_string()),
+            ],
+            assets
+        );
+    }
+
+    #[test]
+    fn test_math() {
+        let tex = "<annotation encoding=\"application/x-tex\">";
+        let cases = [
+            (
+                "中文$x$中文",
+                format!(
+                    "<p>中文\u{2009}<math><semantics><mi>x</mi>{}x</annotation></semantics></math>\
+                     \u{2009}中文</p>\n",
+                    tex
+                ),
+            ),
+            (
+                "$$a<b$$",
+                format!(
+                    "<p><math display=\"block\"><semantics><mrow><mi>a</mi><mo>&lt;</mo><mi>b</mi>\
+                     </mrow>{}a&lt;b</annotation></semantics></math></p>\n",
+                    tex
+                ),
+            ),
+            (
+                "公式$\\foo <b>$",
+                "<p>公式\u{2009}<code class=\"tex\">\\foo &lt;b&gt;</code></p>\n".to_string(),
+            ),
+            ("$5 and $6", "<p>$5 and $6</p>\n".to_string()),
+        ];
+        for &(input, ref output) in cases.iter() {
+            assert_eq!(*output, render(input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_notes() {
+        let input = "中文[^1]Rust，Rust[^a]中文[^1]\n\n\
+                     [^a]: 注释\n\n\
+                     [^1]: 多段\n\n    ```\n    code\n    ```\n\n\
+                     [^u]: 未引用\n\n\
+                     第二段\n";
+        let body = "<p>中文\u{2060}<sup id=\"fnref-post-1-1\"><a href=\"#fn-post-1\">1</a></sup>\
+                    \u{2009}Rust，Rust\u{2060}<sup id=\"fnref-post-2-1\"><a href=\"#fn-post-2\">2</a></sup>\
+                    \u{2009}中文\u{2060}<sup id=\"fnref-post-1-2\"><a href=\"#fn-post-1\">1</a></sup></p>\n";
+        let first = "<p>多段</p>\n<pre><code>code\n</code></pre>\n\
+                     <p><a href=\"#fnref-post-1-1\">\u{21A9}\u{FE0E}</a> \
+                     <a href=\"#fnref-post-1-2\">\u{21A9}\u{FE0E}</a></p>\n";
+        let second = "<p>注释 <a href=\"#fnref-post-2-1\">\u{21A9}\u{FE0E}</a></p>\n";
+        assert_eq!(
+            format!(
+                "{}<p>第二段</p>\n<section class=\"footnotes\">\n<ol>\n\
+                 <li id=\"fn-post-1\">\n{}</li>\n<li id=\"fn-post-2\">\n{}</li>\n</ol>\n</section>\n",
+                body, first, second
+            ),
+            render(input)
+        );
+
+        let content = format!(
+            "# Title\n\n    本文发表于：2018-01-01\n    页名：post\n\n{}",
+            input
+        );
+        let post = Blog::from(&content).next().unwrap();
+        assert_eq!(vec!["footnote [^u] is never cited"], post.warnings);
+        let post = Blog::from("# Title\n\n    页名：post\n\n中文[^x]\n")
+            .next()
+            .unwrap();
+        assert_eq!(vec!["footnote [^x] is not defined"], post.warnings);
+
+        let mut blog = Blog::from(&content);
+        blog.placement = Notes::Side;
+        assert_eq!(
+            format!(
+                "{}<aside class=\"sidenote\" id=\"fn-post-1\"><sup>1</sup>\n{}</aside>\n\
+                 <aside class=\"sidenote\" id=\"fn-post-2\"><sup>2</sup>\n{}</aside>\n<p>第二段</p>\n",
+                body, first, second
+            ),
+            blog.next().unwrap().data
+        );
+    }
+
+    #[test]
+    fn test_front() {
+        let yaml = "---\ntitle: Rust中文\ndate: 2018-01-01T12:45:00+08:00\n\
+                    categories: [category]\ntags:\n  - a\n  - b\nlang: en\n---\n\n\
+                    Rust\n\n# 标题\n";
+        let toml = "+++\ntitle = \"Rust\"\ndate = 2018-01-01\nlastmod = 2018-01-12\n\
+                    slug = \"rust\"\n+++\n\nRust\n";
+        let mut posts = Blog::from(yaml).path(Path::new("posts/slug.md"));
+        let post = posts.next().unwrap();
+        assert!(posts.next().is_none());
+        assert_eq!("Rust\u{2009}中文", post.title);
+        assert_eq!("2018-01-01T12:45:00+08:00", post.released);
+        assert_eq!(post.released, post.modified);
+        assert_eq!("category", post.category);
+        assert_eq!(vec!["a", "b"], post.tags);
+        assert_eq!("slug", post.pagename);
+        assert_eq!(Lang::English, post.lang);
+        assert_eq!("<p>Rust</p>\n<h1>标题</h1>\n", post.data);
+
+        let post = Blog::from(toml).next().unwrap();
+        assert_eq!("Rust", post.title);
+        assert_eq!("2018-01-01", post.released);
+        assert_eq!("2018-01-12", post.modified);
+        assert_eq!("rust", post.pagename);
+        assert_eq!("<p>Rust</p>\n", post.data);
+    }
+}
@@ -9,17 +9,25 @@
 //!
 //! Site::new(blog).render();
 //! ```
-use parser::{Blog, Post};
+use asset::copy;
+use config::{FEED_HYPHENS, LANG, LINTER, PAGE_HYPHENS, SITE};
+use escape::{Attr, Cdata, Text, Url};
+use lang::tag;
+use linter::{Hyphens, Spacing};
+use parser::Post;
 use rayon::prelude::*;
 use rayon::scope;
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashSet};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::{BufWriter, Write};
 use std::path::PathBuf;
 
 const PAGESIZE: usize = 7;
-const DESTROOT: &str = "public";
+/// Related posts listed under a post at most.
+const RELATED: usize = 5;
+/// Where the site goes, and where site-absolute images are read from.
+pub const DESTROOT: &str = "public";
 
 impl Ord for Post {
     fn cmp(&self, other: &Post) -> Ordering {
@@ -29,10 +37,129 @@ impl Ord for Post {
 
 impl PartialOrd for Post {
     fn partial_cmp(&self, other: &Post) -> Option<Ordering> {
-        Some(self.modified.cmp(&other.modified))
+        Some(self.cmp(other))
     }
 }
 
+/// Opening tag of article, enabling the CSS fallbacks for what the linter leaves out.
+fn article() -> &'static str {
+    match (
+        PAGE_HYPHENS == Hyphens::None,
+        LINTER.spacing == Spacing::None,
+    ) {
+        (false, false) => "<article>\n",
+        (true, false) => "<article class=\"hyphens\">\n",
+        (false, true) => "<article class=\"autospace\">\n",
+        (true, true) => "<article class=\"hyphens autospace\">\n",
+    }
+}
+
+/// Returns if text starts with a date like 2018-01-01.
+fn is_date(text: &str) -> bool {
+    text.len() >= 10
+        && text.bytes().take(10).enumerate().all(|(idx, x)| match idx {
+            4 | 7 => x == b'-',
+            _ => x.is_ascii_digit(),
+        })
+}
+
+/// Returns the day of a date, or the date if it is too short for one.
+fn day(date: &str) -> &str {
+    date.get(..10).unwrap_or(date)
+}
+
+/// Returns the site-absolute URL of post.
+fn url(post: &Post) -> String {
+    if post.category.is_empty() {
+        format!("/{}/", Url(&post.pagename))
+    } else {
+        format!("/{}/{}/", Url(&post.category), Url(&post.pagename))
+    }
+}
+
+/// Resolve the cross-references `post:pagename` to the URL of the post, and to its title where the
+/// link has no text. Dangling and ambiguous ones are reported by Site::errors.
+fn resolve(blog: &mut [Post]) {
+    let posts: HashMap<String, (String, String)> = blog
+        .iter()
+        .map(|x| (x.pagename.clone(), (url(x), x.title.clone())))
+        .collect();
+    for post in blog.iter_mut() {
+        for name in &post.refs {
+            if let Some((url, title)) = posts.get(name) {
+                let name = Url(name);
+                post.data = post
+                    .data
+                    .replace(&format!("href=\"post:{}\"", name), &format!("href=\"{}\"", url))
+                    .replace(&format!("href=\"post:{}#", name), &format!("href=\"{}#", url))
+                    .replace(&format!("<!--post:{}-->", name), title);
+            }
+        }
+    }
+}
+
+/// Returns the other posts each post links to, by the URLs in its data.
+fn links(blog: &[Post]) -> Vec<Vec<usize>> {
+    let posts: HashMap<String, usize> = blog
+        .iter()
+        .enumerate()
+        .map(|(idx, post)| (url(post), idx))
+        .collect();
+    let mut result = vec![Vec::new(); blog.len()];
+    for (idx, post) in blog.iter().enumerate() {
+        for href in post.data.split("href=\"").skip(1) {
+            let href = href.split(['"', '#', '?']).next().unwrap();
+            let mut href = href.strip_prefix(SITE).unwrap_or(href).to_string();
+            if !href.ends_with('/') {
+                href.push('/');
+            }
+            match posts.get(&href) {
+                Some(&x) if x != idx && !result[idx].contains(&x) => result[idx].push(x),
+                _ => (),
+            }
+        }
+    }
+    result
+}
+
+/// Returns the posts related to each post, the most related and then the latest first.
+///
+/// Posts linked either way are the closest, then ones sharing tags, then ones linked with a same
+/// post and ones in the same category.
+fn related(blog: &[Post], links: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut linked = vec![HashSet::new(); blog.len()];
+    for (idx, targets) in links.iter().enumerate() {
+        for &x in targets {
+            linked[idx].insert(x);
+            linked[x].insert(idx);
+        }
+    }
+    let score = |a: usize, b: usize| {
+        let (x, y) = (&blog[a], &blog[b]);
+        let mut score = 2 * x.tags.iter().filter(|&tag| y.tags.contains(tag)).count();
+        if linked[a].contains(&b) {
+            score += 3;
+        } else if !linked[a].is_disjoint(&linked[b]) {
+            score += 1;
+        }
+        if !x.category.is_empty() && x.category == y.category {
+            score += 1;
+        }
+        score
+    };
+    (0..blog.len())
+        .map(|a| {
+            let mut scores: Vec<(usize, usize)> = (0..blog.len())
+                .filter(|&b| b != a)
+                .map(|b| (score(a, b), b))
+                .filter(|x| x.0 > 0)
+                .collect();
+            scores.sort_by(|x, y| y.0.cmp(&x.0).then_with(|| blog[y.1].cmp(&blog[x.1])));
+            scores.into_iter().take(RELATED).map(|x| x.1).collect()
+        })
+        .collect()
+}
+
 fn create(path: PathBuf) -> BufWriter<File> {
     if let Some(dir) = path.parent() {
         fs::create_dir_all(dir).unwrap();
@@ -42,13 +169,29 @@ fn create(path: PathBuf) -> BufWriter<File> {
 
 pub struct Site {
     blog: Vec<Post>,
+    /// Posts linking to each post.
+    backlinks: Vec<Vec<usize>>,
+    /// Posts related to each post.
+    related: Vec<Vec<usize>>,
 }
 
 impl Site {
-    pub fn new(blog: Blog) -> Site {
+    pub fn new<I: Iterator<Item = Post>>(blog: I) -> Site {
         timer!("Parser");
+        let mut blog: Vec<Post> = blog.collect();
+        resolve(&mut blog);
+        let links = links(&blog);
+        let mut backlinks = vec![Vec::new(); blog.len()];
+        for (idx, targets) in links.iter().enumerate() {
+            for &x in targets {
+                backlinks[x].push(idx);
+            }
+        }
+        let related = related(&blog, &links);
         Site {
-            blog: blog.collect(),
+            blog,
+            backlinks,
+            related,
         }
     }
 
@@ -64,11 +207,84 @@ impl Site {
             s.spawn(|_| self.render_feed());
             s.spawn(|_| self.render_site());
             s.spawn(|_| self.render_misc());
+            s.spawn(|_| self.render_assets());
         })
     }
 
+    /// Returns a message for each pagename or date missing or malformed, each asset whose file is missing or
+    /// whose target another file would overwrite, each dangling or ambiguous cross-reference, and
+    /// each post whose page another one would overwrite, prefixed by the file and the pagename.
+    pub fn errors(&self) -> Vec<String> {
+        let mut pagenames: HashMap<&str, Vec<String>> = HashMap::new();
+        for post in &self.blog {
+            pagenames.entry(&post.pagename).or_default().push(url(post));
+        }
+        let mut pages = HashMap::new();
+        let mut targets = HashMap::new();
+        let mut result = Vec::new();
+        for post in &self.blog {
+            let at = if post.pagename.is_empty() {
+                result.push(format!("{}: the pagename is missing", post.path.display()));
+                post.path.display().to_string()
+            } else {
+                format!("{}: {}", post.path.display(), post.pagename)
+            };
+            if post.released.is_empty() {
+                result.push(format!("{}: the released date is missing", at));
+            }
+            for date in [&post.released, &post.modified].iter() {
+                if !date.is_empty() && !is_date(date) {
+                    result.push(format!("{}: {} is not a date", at, date));
+                }
+            }
+            for asset in post.assets.iter().filter(|x| !x.source.is_file()) {
+                result.push(format!("{}: {} is missing", at, asset.source.display()));
+            }
+            for asset in &post.assets {
+                let other = *targets.entry(&asset.target).or_insert(&asset.source);
+                if *other != asset.source {
+                    result.push(format!(
+                        "{}: {} and {} are both copied to {}",
+                        at,
+                        other.display(),
+                        asset.source.display(),
+                        asset.url()
+                    ));
+                }
+            }
+            for name in &post.refs {
+                match pagenames.get(name.as_str()) {
+                    None => result.push(format!("{}: post:{} is not found", at, name)),
+                    Some(urls) if urls.len() > 1 => result.push(format!(
+                        "{}: post:{} is ambiguous between {}",
+                        at,
+                        name,
+                        urls.join(" and ")
+                    )),
+                    _ => (),
+                }
+            }
+            if let Some(other) = pages.insert(url(post), &post.path) {
+                result.push(format!("{}: the page is taken by {}", at, other.display()));
+            }
+        }
+        result
+    }
+
+    /// Returns the warnings of each post, prefixed by the file and the pagename.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut result = Vec::new();
+        for post in &self.blog {
+            for warning in &post.warnings {
+                let path = post.path.display();
+                result.push(format!("{}: {}: {}", path, post.pagename, warning));
+            }
+        }
+        result
+    }
+
     fn render_post(&self) {
-        self.blog.par_iter().for_each(|post| {
+        self.blog.par_iter().enumerate().for_each(|(idx, post)| {
             let path = [DESTROOT, &post.category, &post.pagename, "index.html"]
                 .iter()
                 .collect();
@@ -76,10 +292,10 @@ impl Site {
             wite!(
                 w,
                 "<!DOCTYPE html>\n"
-                "<html lang=\"cmn-Hans\">\n"
+                "<html lang=\""(post.lang.as_str())"\">\n"
                 "<head>\n"
                 "<meta charset=\"UTF-8\">\n"
-                "<title>"(post.title)"</title>\n"
+                "<title>"(PAGE_HYPHENS.plain().apply(&post.title))"</title>\n"
                 "<meta name=\"author\" content=\"Daniel Zeng\">\n"
                 "<meta name=\"viewport\" content=\"width=device-width,initial-scale=1,maximum-scale=1,user-scalable=no\">\n"
                 "<link rel=\"stylesheet\" type=\"text/css\" href=\"/mono.css\">\n"
@@ -90,15 +306,33 @@ impl Site {
                 "<header>\n"
                 "<a href=\"/\"><h1>DarkNode</h1><h2>Life, the Universe and Everything</h2></a>\n"
                 "</header>\n"
-                "<article>\n"
-                "<h1>"(post.title)"</h1>\n"
-                "<pre><code>本文发表于：<time datetime=\""(post.released)"\">"(&post.released[0..10])"</time>\n"
-                "最后修改于：<time datetime=\""(post.modified)"\">"(&post.modified[0..10])"</time>\n"
+                (article())
+                "<h1>"(PAGE_HYPHENS.apply(&post.title))"</h1>\n"
+                "<pre><code>本文发表于：<time datetime=\""(Attr(&post.released))"\">"(Text(day(&post.released)))"</time>\n"
+                "最后修改于：<time datetime=\""(Attr(&post.modified))"\">"(Text(day(&post.modified)))"</time>\n"
                 if !post.category.is_empty() {
-                    "分类：<a href=\"/"(post.category)"/\">"(post.category)"</a>\n"
+                    "分类：<a href=\"/"(Url(&post.category))"/\">"(Text(&post.category))"</a>\n"
+                }
+                if !post.tags.is_empty() {
+                    "标签："(Text(&post.tags.join("、")))"\n"
                 }
                 "合计信息量："{((post.data.chars().count() as f64) / 1024.0):.2}"kb</code></pre>\n"
-                (post.data)
+                (PAGE_HYPHENS.apply(&tag(&post.data, post.lang)))
+                for &(class, name, list) in [
+                    ("backlinks", "引用本文", &self.backlinks[idx]),
+                    ("related", "相关文章", &self.related[idx]),
+                ].iter() {
+                    if !list.is_empty() {
+                        "<aside class=\""(class)"\">\n"
+                        "<h2>"(name)"</h2>\n"
+                        "<ul>\n"
+                        for &x in list.iter() {
+                            "<li><a href=\""(url(&self.blog[x]))"\">"(PAGE_HYPHENS.apply(&self.blog[x].title))"</a></li>\n"
+                        }
+                        "</ul>\n"
+                        "</aside>\n"
+                    }
+                }
                 "</article>\n"
                 "<footer>\n"
                 "<p>&copy;&nbsp;2014-2018&nbsp;<a href=\"/about/\">Daniel Zeng</a>&nbsp;</p>\n"
@@ -124,7 +358,7 @@ impl Site {
             wite!(
                 w,
                 "<!DOCTYPE html>\n"
-                "<html lang=\"cmn-Hans\">\n"
+                "<html lang=\""(LANG.as_str())"\">\n"
                 "<head>\n"
                 "<meta charset=\"UTF-8\">\n"
                 "<title>DarkNode</title>\n"
@@ -139,11 +373,11 @@ impl Site {
                 "<a href=\"/\"><h1>DarkNode</h1><h2>Life, the Universe and Everything</h2></a>\n"
                 "</header>\n"
                 "<article>\n"
-                "<nav>分类 - "(category)"</nav>"
+                "<nav>分类 - "(Text(category))"</nav>"
                 for post in self.blog.iter().filter(|x| x.category == category) {
                     "<section>\n"
-                    "<a href=\"/"(post.category)"/"(post.pagename)"/\">"
-                    "<h1>"(post.title)"</h1><time datetime=\""(post.released)"\">"(&post.released[0..10])"</time>"
+                    "<a href=\"/"(Url(&post.category))"/"(Url(&post.pagename))"/\">"
+                    "<h1>"(PAGE_HYPHENS.apply(&post.title))"</h1><time datetime=\""(Attr(&post.released))"\">"(Text(day(&post.released)))"</time>"
                     "</a>\n"
                     "</section>\n"
                 }
@@ -177,7 +411,7 @@ impl Site {
             wite!(
                 w,
                 "<!DOCTYPE html>\n"
-                "<html lang=\"cmn-Hans\">\n"
+                "<html lang=\""(LANG.as_str())"\">\n"
                 "<head>\n"
                 "<meta charset=\"UTF-8\">\n"
                 "<title>DarkNode</title>\n"
@@ -209,8 +443,8 @@ impl Site {
                     .skip((pid - 1) * PAGESIZE)
                     .take(PAGESIZE) {
                     "<section>\n"
-                    "<a href=\"/"(post.category)"/"(post.pagename)"/\">"
-                    "<h1>"(post.title)"</h1><time datetime=\""(post.released)"\">"(&post.released[0..10])"</time>"
+                    "<a href=\"/"(Url(&post.category))"/"(Url(&post.pagename))"/\">"
+                    "<h1>"(PAGE_HYPHENS.apply(&post.title))"</h1><time datetime=\""(Attr(&post.released))"\">"(Text(day(&post.released)))"</time>"
                     "</a>\n"
                     "</section>\n"
                 }
@@ -235,23 +469,23 @@ impl Site {
             "<title>DarkNode</title>\n"
             "<subtitle>Life, the Universe and Everything</subtitle>\n"
             "<link href=\"/atom.xml\" rel=\"self\"/>\n"
-            "<link href=\"https://darknode.in/\"/>\n"
-            "<updated>"(posts.peek().map_or("", |post| &post.modified))"</updated>\n"
-            "<id>https://darknode.in/</id>\n"
+            "<link href=\""(SITE)"/\"/>\n"
+            "<updated>"(Text(posts.peek().map_or("", |post| &post.modified)))"</updated>\n"
+            "<id>"(SITE)"/</id>\n"
             "<author>\n"
             "<name>Daniel Zeng</name>\n"
             "</author>\n"
             for _ in 0..3 {
                 if let Some(post) = posts.pop() {
-                    "<entry>\n"
-                    "<title>"(post.title)"</title>\n"
-                    "<link href=\"https://darknode.in/"(post.category)"/"(post.pagename)"/\"/>\n"
-                    "<id>https://darknode.in/"(post.category)"/"(post.pagename)"/</id>\n"
-                    "<published>"(post.released)"</published>\n"
-                    "<updated>"(post.modified)"</updated>\n"
+                    "<entry xml:lang=\""(post.lang.as_str())"\">\n"
+                    "<title>"(FEED_HYPHENS.plain().apply(&post.title))"</title>\n"
+                    "<link href=\""(SITE)"/"(Url(&post.category))"/"(Url(&post.pagename))"/\"/>\n"
+                    "<id>"(SITE)"/"(Url(&post.category))"/"(Url(&post.pagename))"/</id>\n"
+                    "<published>"(Text(&post.released))"</published>\n"
+                    "<updated>"(Text(&post.modified))"</updated>\n"
                     "<content type=\"html\">\n"
                     "<![CDATA[\n"
-                    (post.data)
+                    (Cdata(&FEED_HYPHENS.apply(&tag(&post.data, post.lang))))
                     "]]>\n"
                     "</content>\n"
                     "</entry>\n"
@@ -268,7 +502,7 @@ impl Site {
             w,
             "User-agent: *\n"
             "Allow: /\n"
-            "Sitemap: https://darknode.in/sitemap.xml\n"
+            "Sitemap: "(SITE)"/sitemap.xml\n"
         ).unwrap();
 
         let path = [DESTROOT, "sitemap.xml"].iter().collect();
@@ -280,12 +514,12 @@ impl Site {
             for pid in 1..self.blog.len() / PAGESIZE + 2 {
                 "<url>\n"
                 if pid == 1 {
-                    "<loc>https://darknode.in/\n"
+                    "<loc>"(SITE)"/\n"
                 } else {
-                    "<loc>https://darknode.in/page/"(pid)"/\n"
+                    "<loc>"(SITE)"/page/"(pid)"/\n"
                 }
                 "<lastmod>"
-                (self.blog.iter().take(pid * PAGESIZE).max().map_or("", |post| &post.modified))
+                (Text(self.blog.iter().take(pid * PAGESIZE).max().map_or("", |post| &post.modified)))
                 "</lastmod>\n"
                 "<changefreq>weekly</changefreq>\n"
                 "<priority>1.0</priority>\n"
@@ -294,11 +528,11 @@ impl Site {
             for post in &self.blog {
                 "<url>\n"
                 if !post.category.is_empty() {
-                    "<loc>https://darknode.in/"(post.category)"/"(post.pagename)"/</loc>\n"
+                    "<loc>"(SITE)"/"(Url(&post.category))"/"(Url(&post.pagename))"/</loc>\n"
                 } else {
-                    "<loc>https://darknode.in/"(post.pagename)"/</loc>\n"
+                    "<loc>"(SITE)"/"(Url(&post.pagename))"/</loc>\n"
                 }
-                "<lastmod>"(post.modified)"</lastmod>\n"
+                "<lastmod>"(Text(&post.modified))"</lastmod>\n"
                 "<changefreq>monthly</changefreq>\n"
                 if !post.category.is_empty() {
                     "<priority>0.8</priority>\n"
@@ -311,6 +545,18 @@ impl Site {
         ).unwrap();
     }
 
+    fn render_assets(&self) {
+        let mut targets = HashSet::new();
+        let assets: Vec<_> = self
+            .blog
+            .iter()
+            .flat_map(|post| post.assets.iter())
+            .filter(|x| targets.insert(&x.target))
+            .cloned()
+            .collect();
+        copy(&assets);
+    }
+
     fn render_misc(&self) {
         let path = [DESTROOT, "mono.css"].iter().collect();
         let mut w = create(path);
@@ -321,3 +567,106 @@ impl Site {
         w.write_all(include_bytes!("favicon.png")).unwrap();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{day, Site};
+    use parser::Blog;
+    use std::path::Path;
+
+    #[test]
+    fn test_resolve() {
+        let meta = "    本文发表于：2018-01-01\n";
+        let content = format!(
+            "# 中文\n\n{0}    分类：c\n    页名：a\n\n[上文](post:b#x)与[](post:b)\n\n\
+             # Rust\n\n{0}    页名：b\n\n[](post:a)[](post:gone)\n",
+            meta
+        );
+        let site = Site::new(Blog::from(&content));
+        assert_eq!(
+            "<p><a href=\"/b/#x\">上文</a>与<a href=\"/b/\">Rust</a></p>\n",
+            site.blog[0].data
+        );
+        assert_eq!(
+            "<p><a href=\"/c/a/\">中文</a><a href=\"post:gone\"><!--post:gone--></a></p>\n",
+            site.blog[1].data
+        );
+        assert_eq!(vec!["-: b: post:gone is not found"], site.errors());
+
+        let content = format!(
+            "# A\n\n{0}    分类：c\n    页名：a\n\n[](post:b)\n\n\
+             # B\n\n{0}    分类：c\n    页名：b\n\n正文\n\n\
+             # B\n\n{0}    分类：d\n    页名：b\n\n正文\n",
+            meta
+        );
+        let site = Site::new(Blog::from(&content));
+        assert_eq!(
+            vec!["-: a: post:b is ambiguous between /c/b/ and /d/b/"],
+            site.errors()
+        );
+    }
+
+    #[test]
+    fn test_assets() {
+        let content = "# A\n\n    本文发表于：2018-01-01\n    页名：a\n\n\
+                       ![](img/a.png)![](other/a.png)![](img/a.png)\n";
+        let site = Site::new(Blog::from(content).path(Path::new("posts/a.md")));
+        assert_eq!(
+            vec![
+                "posts/a.md: a: posts/img/a.png is missing",
+                "posts/a.md: a: posts/other/a.png is missing",
+                "posts/a.md: a: posts/img/a.png and posts/other/a.png are both copied to /a/a.png",
+            ],
+            site.errors()
+        );
+    }
+
+    #[test]
+    fn test_missing() {
+        let cases = [
+            (
+                "---\ntitle: Rust\nslug: rust\n---\n\n正文\n",
+                vec!["-: rust: the released date is missing"],
+            ),
+            (
+                "---\ntitle: Rust\ndate: 2018-01-01\n---\n\n正文\n",
+                vec!["-: the pagename is missing"],
+            ),
+            (
+                "---\ndate: 2018-1-1\nlastmod: 2018-01-02T00:00:00Z\nslug: rust\n---\n",
+                vec!["-: rust: 2018-1-1 is not a date"],
+            ),
+            (
+                "# Rust\n\n    本文发表于：2018-01-01\n    页名：rust\n",
+                vec![],
+            ),
+        ];
+        for (input, output) in cases.iter() {
+            let site = Site::new(Blog::from(input));
+            assert_eq!(output, &site.errors(), "{}", input);
+        }
+        assert_eq!("2018-01-01", day("2018-01-01T12:45:00+08:00"));
+        assert_eq!("2018-1-1", day("2018-1-1"));
+    }
+
+    #[test]
+    fn test_graph() {
+        let meta = "    本文发表于：2018-01-01\n";
+        let content = format!(
+            "# A\n\n{0}    最后修改于：2018-01-01\n    分类：c\n    页名：a\n\n[B](post:b)\n\n\
+             # B\n\n{0}    最后修改于：2018-01-02\n    分类：c\n    页名：b\n    标签：x\n\n\
+             [C](https://darknode.in/c)\n\n\
+             # C\n\n{0}    最后修改于：2018-01-03\n    页名：c\n    标签：x、y\n\n正文\n\n\
+             # D\n\n{0}    最后修改于：2018-01-04\n    分类：c\n    页名：d\n\n正文\n",
+            meta
+        );
+        let site = Site::new(Blog::from(&content));
+        assert_eq!(vec![vec![], vec![0], vec![1], vec![]], site.backlinks);
+        // B is linked and in the category of A, then the later of D in the category and C linked
+        // with B.
+        assert_eq!(vec![1, 3, 2], site.related[0]);
+        assert_eq!(vec![2, 0, 3], site.related[1]);
+        assert_eq!(vec![1, 0], site.related[2]);
+        assert_eq!(vec![1, 0], site.related[3]);
+    }
+}
@@ -0,0 +1,176 @@
+//! Diagnostics about the source, printable as text or JSON.
+//!
+//! # Example
+//!
+//! ```
+//! use report::{write_all, Format, Report};
+//!
+//! let mut report = Report::new(&data);
+//! report.push(offset, "trailing", "trailing whitespace".to_string());
+//! write_all(&mut stdout(), &[("ulysses.md", &report)], Format::Text).unwrap();
+//! ```
+use std::fmt;
+use std::io::{self, Write};
+
+/// A finding at line and column of the source, both counted from 1, columns in chars.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub rule: &'static str,
+    pub message: String,
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+/// Diagnostics of a source, located by byte offsets.
+pub struct Report<'a> {
+    source: &'a str,
+    lines: Vec<usize>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Report<'a> {
+    pub fn new(source: &'a str) -> Report<'a> {
+        let lines = Some(0)
+            .into_iter()
+            .chain(source.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        Report {
+            source,
+            lines,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Returns the line and column of byte offset in source.
+    pub fn locate(&self, offset: usize) -> (usize, usize) {
+        let line = match self.lines.binary_search(&offset) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        };
+        let column = self.source[self.lines[line]..offset].chars().count();
+        (line + 1, column + 1)
+    }
+
+    pub fn push(&mut self, offset: usize, rule: &'static str, message: String) {
+        let (line, column) = self.locate(offset);
+        self.diagnostics.push(Diagnostic {
+            line,
+            column,
+            rule,
+            message,
+        });
+    }
+
+    /// Sort diagnostics by where they are.
+    pub fn sort(&mut self) {
+        self.diagnostics.sort_by_key(|x| (x.line, x.column));
+    }
+}
+
+/// Write the diagnostics of each source named by its path into w, as one list.
+pub fn write_all<W: Write>(
+    w: &mut W,
+    reports: &[(&str, &Report)],
+    format: Format,
+) -> io::Result<()> {
+    let diagnostics = reports
+        .iter()
+        .flat_map(|&(path, report)| report.diagnostics.iter().map(move |d| (path, d)));
+    match format {
+        Format::Text => {
+            for (path, d) in diagnostics {
+                writeln!(
+                    w,
+                    "{}:{}:{}: {}: {}",
+                    path, d.line, d.column, d.rule, d.message
+                )?;
+            }
+        }
+        Format::Json => {
+            w.write_all(b"[")?;
+            for (i, (path, d)) in diagnostics.enumerate() {
+                if i != 0 {
+                    w.write_all(b",")?;
+                }
+                write!(
+                    w,
+                    "\n  {{\"file\": {}, \"line\": {}, \"column\": {}, \"rule\": {}, \"message\": {}}}",
+                    Json(path),
+                    d.line,
+                    d.column,
+                    Json(d.rule),
+                    Json(&d.message)
+                )?;
+            }
+            w.write_all(b"\n]\n")?;
+        }
+    }
+    Ok(())
+}
+
+/// A JSON string literal when displayed.
+struct Json<'a>(&'a str);
+
+impl<'a> fmt::Display for Json<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("\"")?;
+        for ch in self.0.chars() {
+            match ch {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                '\t' => f.write_str("\\t")?,
+                '\u{0000}'..='\u{001F}' => write!(f, "\\u{:04x}", ch as u32)?,
+                _ => write!(f, "{}", ch)?,
+            }
+        }
+        f.write_str("\"")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_all, Format, Report};
+
+    #[test]
+    fn test_report() {
+        let mut report = Report::new("Rust \n");
+        report.push(4, "trailing", "trailing whitespace".to_string());
+        report.push(1, "quote", "\"R\\ust\"".to_string());
+        let mut other = Report::new("\nb\t\n");
+        other.push(2, "trailing", "trailing whitespace".to_string());
+        let reports = [("a.md", &report), ("b.md", &other)];
+
+        let mut result = Vec::new();
+        write_all(&mut result, &reports, Format::Text).unwrap();
+        assert_eq!(
+            "a.md:1:5: trailing: trailing whitespace\n\
+             a.md:1:2: quote: \"R\\ust\"\n\
+             b.md:2:2: trailing: trailing whitespace\n",
+            String::from_utf8(result).unwrap()
+        );
+
+        let mut result = Vec::new();
+        write_all(&mut result, &reports, Format::Json).unwrap();
+        assert_eq!(
+            "[\n  {\"file\": \"a.md\", \"line\": 1, \"column\": 5, \"rule\": \"trailing\", \
+             \"message\": \"trailing whitespace\"},\n  \
+             {\"file\": \"a.md\", \"line\": 1, \"column\": 2, \"rule\": \"quote\", \
+             \"message\": \"\\\"R\\\\ust\\\"\"},\n  \
+             {\"file\": \"b.md\", \"line\": 2, \"column\": 2, \"rule\": \"trailing\", \
+             \"message\": \"trailing whitespace\"}\n]\n",
+            String::from_utf8(result).unwrap()
+        );
+
+        let mut result = Vec::new();
+        write_all(&mut result, &[], Format::Json).unwrap();
+        assert_eq!("[\n]\n", String::from_utf8(result).unwrap());
+    }
+}
@@ -0,0 +1,566 @@
+//! Allow-list sanitiser for the raw HTML in posts.
+//!
+//! Raw HTML is written through a Sanitizer, which keeps what the Policy allows and drops the rest:
+//!
+//! ```plain
+//! <kbd>Rust</kbd>                  => <kbd>Rust</kbd>          allowed tag
+//! <font color="red">Rust</font>    => Rust                     tag not allowed, content kept
+//! <script>alert(1)</script>        =>                          script and style, content dropped
+//! <a href="/" onclick="x">         => <a href="/">             event handler
+//! <a href="javascript:x" id="a">   => <a id="a">               URL of a scheme not allowed
+//! <!-- note -->                    =>                          comments and declarations
+//! 1 < 2                            => 1 &lt; 2                 a stray `<`
+//! ```
+//!
+//! Everything dropped is a Finding. Scripts, event handlers and URLs running a script, see
+//! unsafe_scheme, are dangerous ones, which a strict Policy refuses to build with. Markdown links
+//! and images go through unsafe_scheme too.
+//!
+//! # Example
+//!
+//! ```
+//! use sanitize::Sanitizer;
+//!
+//! let mut sanitizer = Sanitizer::new(&HTML);
+//! for finding in sanitizer.clean(&mut data, "<a onclick=\"x\">") {
+//!     eprintln!("{}", finding);
+//! }
+//! ```
+use std::fmt;
+
+/// What raw HTML may contain, tag and attribute names in lower case.
+pub struct Policy {
+    pub tags: &'static [&'static str],
+    pub attributes: &'static [&'static str],
+    pub schemes: &'static [&'static str],
+    pub strict: bool,
+}
+
+/// Attributes holding a URL, whose scheme is checked, for each candidate of a `srcset`.
+const URLS: [&str; 9] = [
+    "href",
+    "src",
+    "srcset",
+    "cite",
+    "action",
+    "formaction",
+    "poster",
+    "background",
+    "xlink:href",
+];
+
+/// Elements dropped with their content.
+const DROPPED: [&str; 2] = ["script", "style"];
+
+/// Something dropped at byte offset of the raw HTML.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Finding {
+    pub offset: usize,
+    pub danger: bool,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// A parsed tag, names in lower case.
+struct Tag<'a> {
+    closing: bool,
+    name: String,
+    attributes: Vec<(String, Option<&'a str>)>,
+    empty: bool,
+}
+
+/// Sanitiser of raw HTML pieces, which may split a tag or an element between them.
+pub struct Sanitizer {
+    policy: &'static Policy,
+    /// Unfinished tag at the end of the last piece.
+    pending: String,
+    /// Name of the element whose content is being dropped.
+    skip: Option<&'static str>,
+}
+
+impl Sanitizer {
+    pub fn new(policy: &'static Policy) -> Sanitizer {
+        Sanitizer {
+            policy,
+            pending: String::new(),
+            skip: None,
+        }
+    }
+
+    /// Forget the unfinished tag and element, at the end of a post.
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.skip = None;
+    }
+
+    /// Push what policy allows of html into result, and returns what is dropped.
+    pub fn clean(&mut self, result: &mut String, html: &str) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let joined;
+        let (html, shift) = if self.pending.is_empty() {
+            (html, 0)
+        } else {
+            joined = format!("{}{}", self.pending, html);
+            (joined.as_str(), self.pending.len())
+        };
+        self.pending.clear();
+
+        let mut idx = 0;
+        while idx < html.len() {
+            let rest = &html[idx..];
+            if let Some(name) = self.skip {
+                match find_close(rest, name) {
+                    Some(end) => {
+                        idx += end;
+                        self.skip = None;
+                    }
+                    None => return findings,
+                }
+                continue;
+            }
+            let lt = match rest.find('<') {
+                Some(lt) => lt,
+                None => {
+                    result.push_str(rest);
+                    break;
+                }
+            };
+            result.push_str(&rest[..lt]);
+            idx += lt;
+            let rest = &html[idx..];
+            let offset = idx.saturating_sub(shift);
+            let next = rest[1..].chars().next();
+            let end = if rest.starts_with("<!--") {
+                rest.find("-->").map(|x| x + 3)
+            } else if next == Some('!') || next == Some('?') {
+                rest.find('>').map(|x| x + 1)
+            } else if next.is_some_and(|x| x == '/' || x.is_ascii_alphabetic()) {
+                match parse(rest) {
+                    Some((tag, end)) => {
+                        self.push_tag(result, tag, offset, &mut findings);
+                        Some(end)
+                    }
+                    None => None,
+                }
+            } else {
+                result.push_str("&lt;");
+                Some(1)
+            };
+            match end {
+                Some(end) => idx += end,
+                None => {
+                    self.pending.push_str(rest);
+                    break;
+                }
+            }
+        }
+        findings
+    }
+
+    fn push_tag(
+        &mut self,
+        result: &mut String,
+        tag: Tag,
+        offset: usize,
+        findings: &mut Vec<Finding>,
+    ) {
+        let policy = self.policy;
+        if !policy.tags.contains(&tag.name.as_str()) {
+            let dropped = DROPPED.iter().find(|&&x| x == tag.name);
+            if !tag.closing {
+                findings.push(Finding {
+                    offset,
+                    danger: tag.name == "script",
+                    message: format!("<{}> is not allowed", tag.name),
+                });
+                if !tag.empty {
+                    self.skip = dropped.cloned();
+                }
+            }
+            return;
+        }
+        result.push('<');
+        if tag.closing {
+            result.push('/');
+        }
+        result.push_str(&tag.name);
+        let image = tag.name == "img";
+        for (name, value) in tag.attributes {
+            let message = if name.starts_with("on") {
+                Some((true, format!("event handler {} is not allowed", name)))
+            } else if !policy.attributes.contains(&name.as_str()) {
+                Some((
+                    false,
+                    format!("attribute {} of <{}> is not allowed", name, tag.name),
+                ))
+            } else {
+                let urls: Vec<&str> = match value {
+                    Some(value) if name == "srcset" => value.split(',').collect(),
+                    Some(value) if URLS.contains(&name.as_str()) => vec![value],
+                    _ => Vec::new(),
+                };
+                urls.into_iter()
+                    .filter_map(|url| scheme(url).map(|x| (url, x)))
+                    .find(|(_, x)| !policy.schemes.contains(&x.as_str()))
+                    .map(|(url, scheme)| {
+                        (
+                            unsafe_scheme(url, image).is_some(),
+                            format!("{}: URL of {} is not allowed", scheme, name),
+                        )
+                    })
+            };
+            if let Some((danger, message)) = message {
+                findings.push(Finding {
+                    offset,
+                    danger,
+                    message,
+                });
+                continue;
+            }
+            result.push(' ');
+            result.push_str(&name);
+            if let Some(value) = value {
+                result.push_str("=\"");
+                push_value(result, value);
+                result.push('"');
+            }
+        }
+        if tag.empty {
+            result.push_str(" /");
+        }
+        result.push('>');
+    }
+}
+
+/// Push an attribute value escaped for double quotes, character references are kept.
+fn push_value(result: &mut String, value: &str) {
+    for (idx, ch) in value.char_indices() {
+        match ch {
+            '"' => result.push_str("&#34;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '&' if !is_reference(&value[idx..]) => result.push_str("&amp;"),
+            _ => result.push(ch),
+        }
+    }
+}
+
+/// Returns if text starts with a character reference, named or numeric.
+fn is_reference(text: &str) -> bool {
+    let name = text[1..]
+        .bytes()
+        .take_while(|x| x.is_ascii_alphanumeric() || *x == b'#')
+        .count();
+    name > 0 && text[1 + name..].starts_with(';')
+}
+
+/// Returns the end of the closing tag of name in html, case insensitive.
+fn find_close(html: &str, name: &str) -> Option<usize> {
+    let mut idx = 0;
+    while let Some(lt) = html[idx..].find("</") {
+        let start = idx + lt + 2;
+        let matched = html
+            .get(start..start + name.len())
+            .is_some_and(|x| x.eq_ignore_ascii_case(name));
+        if matched {
+            return html[start..].find('>').map(|x| start + x + 1);
+        }
+        idx = start;
+    }
+    None
+}
+
+/// Parse the tag at the start of html, returns it and its length, None if it is unfinished.
+fn parse(html: &str) -> Option<(Tag<'_>, usize)> {
+    let bytes = html.as_bytes();
+    let closing = bytes.get(1) == Some(&b'/');
+    let mut idx = if closing { 2 } else { 1 };
+    let name = take(html, &mut idx, |x| x.is_ascii_alphanumeric() || x == b'-');
+    let mut tag = Tag {
+        closing,
+        name: name.to_ascii_lowercase(),
+        attributes: Vec::new(),
+        empty: false,
+    };
+    loop {
+        take(html, &mut idx, |x| x.is_ascii_whitespace());
+        match bytes.get(idx) {
+            None => return None,
+            Some(b'>') => return Some((tag, idx + 1)),
+            Some(b'/') => {
+                idx += 1;
+                tag.empty = bytes.get(idx) == Some(&b'>');
+                continue;
+            }
+            _ => (),
+        }
+        let name = take(html, &mut idx, |x| {
+            !(x.is_ascii_whitespace() || x == b'=' || x == b'>' || x == b'/')
+        });
+        take(html, &mut idx, |x| x.is_ascii_whitespace());
+        let value = if bytes.get(idx) == Some(&b'=') {
+            idx += 1;
+            take(html, &mut idx, |x| x.is_ascii_whitespace());
+            match bytes.get(idx) {
+                None => return None,
+                Some(&quote) if quote == b'"' || quote == b'\'' => {
+                    let end = html[idx + 1..].find(quote as char)? + idx + 1;
+                    let value = &html[idx + 1..end];
+                    idx = end + 1;
+                    Some(value)
+                }
+                _ => Some(take(html, &mut idx, |x| {
+                    !(x.is_ascii_whitespace() || x == b'>')
+                })),
+            }
+        } else {
+            None
+        };
+        if !name.is_empty() {
+            tag.attributes.push((name.to_ascii_lowercase(), value));
+        } else if value.is_none() {
+            idx += 1;
+        }
+    }
+}
+
+/// Returns the bytes from idx on matching pred, and moves idx past them.
+fn take<'a, F: Fn(u8) -> bool>(html: &'a str, idx: &mut usize, pred: F) -> &'a str {
+    let start = *idx;
+    while html.as_bytes().get(*idx).is_some_and(|&x| pred(x)) {
+        *idx += 1;
+    }
+    &html[start..*idx]
+}
+
+/// Returns the scheme of url if following it runs a script: `javascript:`, `vbscript:` and
+/// `data:`, but for `data:image/` ones loaded as an image.
+pub fn unsafe_scheme(url: &str, image: bool) -> Option<String> {
+    let scheme = scheme(url)?;
+    let media = url.split_once(':').map_or("", |x| x.1.trim_start());
+    let picture = media
+        .get(..6)
+        .is_some_and(|x| x.eq_ignore_ascii_case("image/"));
+    match scheme.as_str() {
+        "javascript" | "vbscript" => Some(scheme),
+        "data" if !(image && picture) => Some(scheme),
+        _ => None,
+    }
+}
+
+/// Returns the scheme of url in lower case, read the way browsers do: ignoring whitespace and
+/// control chars, and with character references decoded.
+fn scheme(url: &str) -> Option<String> {
+    let mut result = String::new();
+    let mut chars = url.char_indices();
+    while let Some((idx, ch)) = chars.next() {
+        let ch = if ch == '&' {
+            let (decoded, len) = reference(&url[idx..])?;
+            for _ in 1..len {
+                chars.next();
+            }
+            decoded
+        } else {
+            ch
+        };
+        match ch {
+            ':' if !result.is_empty() => return Some(result),
+            _ if ch.is_ascii_whitespace() || ch.is_control() => (),
+            _ if ch.is_ascii_alphanumeric() || ch == '+' || ch == '-' || ch == '.' => {
+                result.push(ch.to_ascii_lowercase())
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Decode the character reference at the start of text, returns it and its length in chars.
+fn reference(text: &str) -> Option<(char, usize)> {
+    let named = [("&colon;", ':'), ("&tab;", '\t'), ("&newline;", '\n')];
+    for &(name, ch) in named.iter() {
+        if text
+            .get(..name.len())
+            .is_some_and(|x| x.eq_ignore_ascii_case(name))
+        {
+            return Some((ch, name.len()));
+        }
+    }
+    let (digits, radix) = match text.strip_prefix("&#") {
+        Some(x) => match x.strip_prefix('x').or_else(|| x.strip_prefix('X')) {
+            Some(x) => (x, 16),
+            None => (x, 10),
+        },
+        None => return None,
+    };
+    let len = digits
+        .bytes()
+        .take_while(|x| (*x as char).is_digit(radix))
+        .count();
+    let code = u32::from_str_radix(&digits[..len], radix).ok()?;
+    let semicolon = digits[len..].starts_with(';') as usize;
+    Some((
+        ::std::char::from_u32(code)?,
+        text.len() - digits.len() + len + semicolon,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Policy, Sanitizer};
+
+    const POLICY: Policy = Policy {
+        tags: &["a", "br", "img", "kbd", "p"],
+        attributes: &["alt", "href", "id", "src", "srcset", "title"],
+        schemes: &["http", "https", "mailto"],
+        strict: true,
+    };
+
+    #[test]
+    fn test_clean() {
+        let cases = [
+            ("<kbd>Rust</kbd>", "<kbd>Rust</kbd>", "", 0),
+            ("<KBD Title=x>", "<kbd title=\"x\">", "", 0),
+            ("<br/><br />", "<br /><br />", "", 0),
+            (
+                "<font color=\"red\">Rust</font>",
+                "Rust",
+                "<font> is not allowed",
+                0,
+            ),
+            (
+                "<script>alert(1)</script>Rust",
+                "Rust",
+                "!<script> is not allowed",
+                0,
+            ),
+            (
+                "a<ScRiPt src=x></sCrIpT>b",
+                "ab",
+                "!<script> is not allowed",
+                1,
+            ),
+            ("<style>p{}</style>", "", "<style> is not allowed", 0),
+            (
+                "<a href=\"/\" onclick=\"x\">",
+                "<a href=\"/\">",
+                "!event handler onclick is not allowed",
+                0,
+            ),
+            (
+                "<img src=x onerror=alert(1)>",
+                "<img src=\"x\">",
+                "!event handler onerror is not allowed",
+                0,
+            ),
+            (
+                "<a href=\"javascript:alert(1)\" id=a>",
+                "<a id=\"a\">",
+                "!javascript: URL of href is not allowed",
+                0,
+            ),
+            (
+                "<a href=\" JaVa\tScRiPt:x\">",
+                "<a>",
+                "!javascript: URL of href is not allowed",
+                0,
+            ),
+            (
+                "<a href=\"javascript&#58;x\">",
+                "<a>",
+                "!javascript: URL of href is not allowed",
+                0,
+            ),
+            (
+                "<a href=\"&#x6A;avascript&colon;x\">",
+                "<a>",
+                "!javascript: URL of href is not allowed",
+                0,
+            ),
+            (
+                "<img src=\"data:image/png;base64,x\">",
+                "<img>",
+                "data: URL of src is not allowed",
+                0,
+            ),
+            (
+                "<a href=\"data:text/html,x\">",
+                "<a>",
+                "!data: URL of href is not allowed",
+                0,
+            ),
+            (
+                "<a href='/a\"b' title=x>",
+                "<a href=\"/a&#34;b\" title=\"x\">",
+                "",
+                0,
+            ),
+            (
+                "<kbd title=\"a>b<c &amp; d&e\">",
+                "<kbd title=\"a&gt;b&lt;c &amp; d&amp;e\">",
+                "",
+                0,
+            ),
+            ("<a href=\"/a:b\">", "<a href=\"/a:b\">", "", 0),
+            (
+                "<img srcset=\"a.png 1x, javascript:x 2x\">",
+                "<img>",
+                "!javascript: URL of srcset is not allowed",
+                0,
+            ),
+            (
+                "<img srcset=\"a.png 1x, https://a.com/b.png 2x\">",
+                "<img srcset=\"a.png 1x, https://a.com/b.png 2x\">",
+                "",
+                0,
+            ),
+            (
+                "<a href=\"https://a.com/\">",
+                "<a href=\"https://a.com/\">",
+                "",
+                0,
+            ),
+            (
+                "<p style=\"x\">",
+                "<p>",
+                "attribute style of <p> is not allowed",
+                0,
+            ),
+            ("<!-- <script> -->a<!DOCTYPE html>", "a", "", 0),
+            ("1 < 2 <3", "1 &lt; 2 &lt;3", "", 0),
+        ];
+        for &(input, output, message, offset) in cases.iter() {
+            let mut sanitizer = Sanitizer::new(&POLICY);
+            let mut result = String::new();
+            let findings = sanitizer.clean(&mut result, input);
+            let found = findings
+                .first()
+                .map(|x| format!("{}{}", if x.danger { "!" } else { "" }, x))
+                .unwrap_or_default();
+            assert_eq!(output, result, "{}", input);
+            assert_eq!(message, found, "{}", input);
+            assert_eq!(
+                offset,
+                findings.first().map_or(0, |x| x.offset),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_pieces() {
+        let mut sanitizer = Sanitizer::new(&POLICY);
+        let mut result = String::new();
+        sanitizer.clean(&mut result, "<p>a<a\n");
+        sanitizer.clean(&mut result, "href=\"/\">b<script>\n");
+        sanitizer.clean(&mut result, "alert(1)\n");
+        sanitizer.clean(&mut result, "</script>c</p>\n");
+        assert_eq!("<p>a<a href=\"/\">bc</p>\n", result);
+    }
+}